@@ -0,0 +1,307 @@
+use fuels::crypto::Hasher;
+use std::fmt;
+
+/// SLIP-44 coin types supported by [encode_address] and [decode_address]
+pub const COIN_TYPE_BTC: u64 = 0;
+pub const COIN_TYPE_ETH: u64 = 60;
+pub const COIN_TYPE_SOL: u64 = 501;
+
+const BASE58_ALPHABET: &[u8] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+const BECH32_CHARSET: &[u8] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+const BECH32_CONST: u32 = 1;
+const BECH32M_CONST: u32 = 0x2bc830a3;
+const BITCOIN_HRP: &str = "bc";
+const BITCOIN_P2PKH_VERSION: u8 = 0x00;
+const BITCOIN_P2SH_VERSION: u8 = 0x05;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AddressCodecError {
+    UnsupportedCoinType(u64),
+    InvalidHex,
+    InvalidLength { expected: usize, actual: usize },
+    InvalidBase58,
+    InvalidBech32,
+    InvalidChecksum,
+    UnsupportedScript,
+}
+
+impl fmt::Display for AddressCodecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AddressCodecError::UnsupportedCoinType(coin_type) => write!(f, "unsupported coin type {}", coin_type),
+            AddressCodecError::InvalidHex => write!(f, "invalid hex address"),
+            AddressCodecError::InvalidLength { expected, actual } => {
+                write!(f, "invalid address length {}, expected {}", actual, expected)
+            }
+            AddressCodecError::InvalidBase58 => write!(f, "invalid base58 address"),
+            AddressCodecError::InvalidBech32 => write!(f, "invalid bech32 address"),
+            AddressCodecError::InvalidChecksum => write!(f, "invalid address checksum"),
+            AddressCodecError::UnsupportedScript => write!(f, "unsupported bitcoin script"),
+        }
+    }
+}
+
+impl std::error::Error for AddressCodecError {}
+
+/// Converts a textual address into the bytes stored by the resolver `set_addr`, the same binary forms as ENS:
+/// - EVM: the 20 address bytes (`0x` prefixed hex, the EIP-55 checksum is not verified)
+/// - Bitcoin: the output script of a base58 P2PKH / P2SH or a bech32 / bech32m segwit address
+/// - Solana: the 32 bytes of the base58 public key
+pub fn encode_address(coin_type: u64, address: &str) -> Result<Vec<u8>, AddressCodecError> {
+    match coin_type {
+        COIN_TYPE_ETH => encode_evm_address(address),
+        COIN_TYPE_BTC => encode_bitcoin_address(address),
+        COIN_TYPE_SOL => {
+            let bytes = base58_decode(address)?;
+            check_length(&bytes, 32)?;
+            Ok(bytes)
+        }
+        _ => Err(AddressCodecError::UnsupportedCoinType(coin_type)),
+    }
+}
+
+/// Reverse of [encode_address], EVM addresses are returned in lowercase
+pub fn decode_address(coin_type: u64, bytes: &[u8]) -> Result<String, AddressCodecError> {
+    match coin_type {
+        COIN_TYPE_ETH => {
+            check_length(bytes, 20)?;
+            Ok(format!("0x{}", hex_encode(bytes)))
+        }
+        COIN_TYPE_BTC => decode_bitcoin_script(bytes),
+        COIN_TYPE_SOL => {
+            check_length(bytes, 32)?;
+            Ok(base58_encode(bytes))
+        }
+        _ => Err(AddressCodecError::UnsupportedCoinType(coin_type)),
+    }
+}
+
+fn check_length(bytes: &[u8], expected: usize) -> Result<(), AddressCodecError> {
+    if bytes.len() != expected {
+        return Err(AddressCodecError::InvalidLength { expected, actual: bytes.len() });
+    }
+    Ok(())
+}
+
+fn encode_evm_address(address: &str) -> Result<Vec<u8>, AddressCodecError> {
+    let hex = address.strip_prefix("0x").ok_or(AddressCodecError::InvalidHex)?;
+    let bytes = hex_decode(hex)?;
+    check_length(&bytes, 20)?;
+    Ok(bytes)
+}
+
+fn hex_encode(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn hex_decode(hex: &str) -> Result<Vec<u8>, AddressCodecError> {
+    if hex.len() % 2 == 1 || !hex.is_ascii() {
+        return Err(AddressCodecError::InvalidHex);
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).map_err(|_| AddressCodecError::InvalidHex))
+        .collect()
+}
+
+fn encode_bitcoin_address(address: &str) -> Result<Vec<u8>, AddressCodecError> {
+    if address.to_lowercase().starts_with("bc1") {
+        let (version, program) = segwit_decode(address)?;
+        // OP_0 or OP_1..OP_16 followed by the push of the witness program
+        let mut script = vec![if version == 0 { 0x00 } else { 0x50 + version }, program.len() as u8];
+        script.extend(program);
+        return Ok(script);
+    }
+    let payload = base58check_decode(address)?;
+    check_length(&payload, 21)?;
+    let hash = &payload[1..];
+    match payload[0] {
+        BITCOIN_P2PKH_VERSION => Ok([&[0x76, 0xa9, 0x14][..], hash, &[0x88, 0xac]].concat()),
+        BITCOIN_P2SH_VERSION => Ok([&[0xa9, 0x14][..], hash, &[0x87]].concat()),
+        _ => Err(AddressCodecError::UnsupportedScript),
+    }
+}
+
+fn decode_bitcoin_script(script: &[u8]) -> Result<String, AddressCodecError> {
+    match script {
+        [0x76, 0xa9, 0x14, hash @ .., 0x88, 0xac] if hash.len() == 20 => {
+            Ok(base58check_encode(&[&[BITCOIN_P2PKH_VERSION][..], hash].concat()))
+        }
+        [0xa9, 0x14, hash @ .., 0x87] if hash.len() == 20 => {
+            Ok(base58check_encode(&[&[BITCOIN_P2SH_VERSION][..], hash].concat()))
+        }
+        [op, length, program @ ..] if (*op == 0x00 || (0x51..=0x60).contains(op)) && *length as usize == program.len() => {
+            let version = if *op == 0x00 { 0 } else { op - 0x50 };
+            segwit_encode(version, program)
+        }
+        _ => Err(AddressCodecError::UnsupportedScript),
+    }
+}
+
+fn double_sha256(data: &[u8]) -> [u8; 32] {
+    *Hasher::hash(*Hasher::hash(data))
+}
+
+fn base58check_encode(payload: &[u8]) -> String {
+    let checksum = double_sha256(payload);
+    base58_encode(&[payload, &checksum[..4]].concat())
+}
+
+fn base58check_decode(address: &str) -> Result<Vec<u8>, AddressCodecError> {
+    let bytes = base58_decode(address)?;
+    if bytes.len() < 4 {
+        return Err(AddressCodecError::InvalidBase58);
+    }
+    let (payload, checksum) = bytes.split_at(bytes.len() - 4);
+    if double_sha256(payload)[..4] != *checksum {
+        return Err(AddressCodecError::InvalidChecksum);
+    }
+    Ok(payload.to_vec())
+}
+
+pub(crate) fn base58_encode(bytes: &[u8]) -> String {
+    base_x_encode(bytes, BASE58_ALPHABET)
+}
+
+pub(crate) fn base58_decode(value: &str) -> Result<Vec<u8>, AddressCodecError> {
+    base_x_decode(value, BASE58_ALPHABET).ok_or(AddressCodecError::InvalidBase58)
+}
+
+/// Big-number encoding used by base58 and base36, leading zero bytes map to the first symbol
+pub(crate) fn base_x_encode(bytes: &[u8], alphabet: &[u8]) -> String {
+    let base = alphabet.len() as u32;
+    // little-endian digits
+    let mut digits: Vec<u8> = Vec::new();
+    for &byte in bytes {
+        let mut carry = byte as u32;
+        for digit in digits.iter_mut() {
+            carry += (*digit as u32) << 8;
+            *digit = (carry % base) as u8;
+            carry /= base;
+        }
+        while carry > 0 {
+            digits.push((carry % base) as u8);
+            carry /= base;
+        }
+    }
+    let zeros = bytes.iter().take_while(|&&byte| byte == 0).count();
+    let mut encoded = (alphabet[0] as char).to_string().repeat(zeros);
+    encoded.extend(digits.iter().rev().map(|&digit| alphabet[digit as usize] as char));
+    encoded
+}
+
+pub(crate) fn base_x_decode(value: &str, alphabet: &[u8]) -> Option<Vec<u8>> {
+    if value.is_empty() {
+        return None;
+    }
+    let base = alphabet.len() as u32;
+    // little-endian bytes
+    let mut bytes: Vec<u8> = Vec::new();
+    for character in value.bytes() {
+        let mut carry = alphabet.iter().position(|&symbol| symbol == character)? as u32;
+        for byte in bytes.iter_mut() {
+            carry += (*byte as u32) * base;
+            *byte = (carry & 0xff) as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.push((carry & 0xff) as u8);
+            carry >>= 8;
+        }
+    }
+    let zeros = value.bytes().take_while(|&character| character == alphabet[0]).count();
+    let mut decoded = vec![0; zeros];
+    decoded.extend(bytes.into_iter().rev());
+    Some(decoded)
+}
+
+fn bech32_polymod(values: &[u8]) -> u32 {
+    const GENERATOR: [u32; 5] = [0x3b6a57b2, 0x26508e6d, 0x1ea119fa, 0x3d4233dd, 0x2a1462b3];
+    let mut checksum: u32 = 1;
+    for &value in values {
+        let top = checksum >> 25;
+        checksum = ((checksum & 0x1ffffff) << 5) ^ value as u32;
+        for (i, generator) in GENERATOR.iter().enumerate() {
+            if (top >> i) & 1 == 1 {
+                checksum ^= generator;
+            }
+        }
+    }
+    checksum
+}
+
+fn bech32_hrp_expand(hrp: &str) -> Vec<u8> {
+    hrp.bytes()
+        .map(|byte| byte >> 5)
+        .chain(std::iter::once(0))
+        .chain(hrp.bytes().map(|byte| byte & 31))
+        .collect()
+}
+
+/// Regroups bits, e.g. bytes into the 5-bit words of bech32 and back
+fn convert_bits(data: &[u8], from: u32, to: u32, pad: bool) -> Result<Vec<u8>, AddressCodecError> {
+    let mut accumulator: u32 = 0;
+    let mut bits: u32 = 0;
+    let max_value: u32 = (1 << to) - 1;
+    let mut result = Vec::new();
+    for &value in data {
+        accumulator = (accumulator << from) | value as u32;
+        bits += from;
+        while bits >= to {
+            bits -= to;
+            result.push(((accumulator >> bits) & max_value) as u8);
+        }
+    }
+    if pad {
+        if bits > 0 {
+            result.push(((accumulator << (to - bits)) & max_value) as u8);
+        }
+    } else if bits >= from || ((accumulator << (to - bits)) & max_value) != 0 {
+        return Err(AddressCodecError::InvalidBech32);
+    }
+    Ok(result)
+}
+
+fn segwit_checksum_const(version: u8) -> u32 {
+    if version == 0 { BECH32_CONST } else { BECH32M_CONST }
+}
+
+fn segwit_encode(version: u8, program: &[u8]) -> Result<String, AddressCodecError> {
+    let mut data = vec![version];
+    data.extend(convert_bits(program, 8, 5, true)?);
+    let mut values = bech32_hrp_expand(BITCOIN_HRP);
+    values.extend(&data);
+    values.extend([0; 6]);
+    let polymod = bech32_polymod(&values) ^ segwit_checksum_const(version);
+    data.extend((0..6).map(|i| ((polymod >> (5 * (5 - i))) & 31) as u8));
+    let encoded: String = data.iter().map(|&value| BECH32_CHARSET[value as usize] as char).collect();
+    Ok(format!("{}1{}", BITCOIN_HRP, encoded))
+}
+
+fn segwit_decode(address: &str) -> Result<(u8, Vec<u8>), AddressCodecError> {
+    if address.to_lowercase() != address && address.to_uppercase() != address {
+        return Err(AddressCodecError::InvalidBech32);
+    }
+    let address = address.to_lowercase();
+    let separator = address.rfind('1').ok_or(AddressCodecError::InvalidBech32)?;
+    let (hrp, data) = (&address[..separator], &address[separator + 1..]);
+    if hrp != BITCOIN_HRP || data.len() < 7 {
+        return Err(AddressCodecError::InvalidBech32);
+    }
+    let data = data
+        .bytes()
+        .map(|character| BECH32_CHARSET.iter().position(|&symbol| symbol == character).map(|value| value as u8))
+        .collect::<Option<Vec<u8>>>()
+        .ok_or(AddressCodecError::InvalidBech32)?;
+    let version = data[0];
+    let mut values = bech32_hrp_expand(hrp);
+    values.extend(&data);
+    if version > 16 || bech32_polymod(&values) != segwit_checksum_const(version) {
+        return Err(AddressCodecError::InvalidChecksum);
+    }
+    let program = convert_bits(&data[1..data.len() - 6], 5, 8, false)?;
+    if program.len() < 2 || program.len() > 40 || (version == 0 && program.len() != 20 && program.len() != 32) {
+        return Err(AddressCodecError::InvalidBech32);
+    }
+    Ok((version, program))
+}
@@ -1,6 +1,6 @@
-use deploy::deployer::{ContractType, DeployResult};
+use deploy::deployer::{ContractType, DeployResult, PausableOperation};
 use deploy::fixture::Fixture;
-use fuels::prelude::ContractId;
+use fuels::prelude::{AssetId, ContractId};
 use maplit::hashmap;
 use std::collections::HashMap;
 use std::str::FromStr;
@@ -26,8 +26,51 @@ async fn main() {
     let (deployer, user) = get_wallets(&config).await;
     let fixture = Fixture::connect(deployer, user, contracts);
 
-    // mint_reserved_domains(fixture).await;
-    call_on_chain_function(fixture).await;
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.first().map(|arg| arg.as_str()) {
+        // e.g. `cargo run --bin tools pause registrar mint`, omit the operation for the global switch
+        Some(command @ ("pause" | "unpause" | "paused")) => toggle_pause(&fixture, command, &args[1..]).await,
+        // e.g. `cargo run --bin tools trust_resolver 0x...`, the deployer must own the registry
+        Some(command @ ("trust_resolver" | "untrust_resolver")) => {
+            let resolver = id(args.get(1).expect("missing resolver id"));
+            fixture.set_trusted_resolver(resolver, command == "trust_resolver").await.unwrap();
+            println!("{:?} trusted: {}", resolver, fixture.is_trusted_resolver(resolver).await);
+        }
+        _ => {
+            // mint_reserved_domains(fixture).await;
+            call_on_chain_function(fixture).await;
+        }
+    }
+}
+
+fn parse_contract(name: &str) -> ContractType {
+    match name {
+        "registrar" => ContractType::Registrar,
+        "registry" => ContractType::Registry,
+        _ => panic!("Invalid contract: {}, must be registrar or registry", name),
+    }
+}
+
+fn parse_operation(name: Option<&String>) -> Option<PausableOperation> {
+    name.map(|name| match name.as_str() {
+        "mint" => PausableOperation::Mint,
+        "renew" => PausableOperation::Renew,
+        "set_resolver" => PausableOperation::SetResolver,
+        "set_primary" => PausableOperation::SetPrimary,
+        _ => panic!("Invalid operation: {}, must be mint, renew, set_resolver or set_primary", name),
+    })
+}
+
+async fn toggle_pause(fixture: &Fixture, command: &str, args: &[String]) {
+    let contract = parse_contract(args.first().expect("missing contract name"));
+    let operation = parse_operation(args.get(1));
+    match command {
+        "pause" => fixture.pause(&contract, operation.clone()).await,
+        "unpause" => fixture.unpause(&contract, operation.clone()).await,
+        _ => {}
+    }
+    let paused = fixture.paused(&contract, operation.clone()).await;
+    println!("{:?} {:?} paused: {}", contract, operation, paused);
 }
 
 fn id(str: &str) -> ContractId {
@@ -43,6 +86,8 @@ async fn call_on_chain_function(fixture: Fixture) {
     let total_assets = fixture.get_total_assets().await;
     println!("Total assets: {}", total_assets);
 
+    show_treasury(&fixture, &[AssetId::BASE]).await;
+
     // let asset_id = fixture.get_domain_asset_id("out.fuel").await;
     // println!("Asset ID: {:?}", asset_id);
     // let uri = fixture.get_domain_uri(asset_id).await;
@@ -51,6 +96,20 @@ async fn call_on_chain_function(fixture: Fixture) {
     // fixture.transfer(&fixture.user, "dino.fuel", &Bech32Address::from_str("fuel1xvwtd4tz3509kugtxx783kd2rrywyqcwper54sku8v7x5hgw7axq6xduf3").unwrap()).await;
 }
 
+async fn show_treasury(fixture: &Fixture, assets: &[AssetId]) {
+    for beneficiary in fixture.get_beneficiaries().await {
+        println!("Beneficiary {:?}: {} bps", beneficiary.recipient, beneficiary.share_bps);
+    }
+    for asset in assets {
+        let report = fixture.get_treasury_report(asset).await;
+        println!("Asset {}:", asset);
+        println!("  collected revenue: {}", report.collected_revenue);
+        println!("  withdrawn: {}", report.withdrawn_funds);
+        println!("  pending withdrawal: {}", report.pending_funds);
+        println!("  pending referral fees: {}", report.pending_referral_fees);
+    }
+}
+
 async fn mint_reserved_domains(fixture: Fixture) {
     let reserved_domains = vec!["wallet", "fuelnameservice", "fns", "fueldomains", "domains", "thunder", "spark", "swaylend", "bsafe", "sway", "fuel", "fuelnetwork"];
     for domain in reserved_domains {
@@ -0,0 +1,198 @@
+use std::fmt;
+
+use crate::address_codec::{base58_decode, base_x_decode, base_x_encode};
+
+/// Multicodec namespaces of EIP-1577 content hashes
+const IPFS_NAMESPACE: u64 = 0xe3;
+const IPNS_NAMESPACE: u64 = 0xe5;
+const ARWEAVE_NAMESPACE: u64 = 0xb29910;
+
+const CID_VERSION: u64 = 1;
+const DAG_PB_CODEC: u64 = 0x70;
+const LIBP2P_KEY_CODEC: u64 = 0x72;
+const ARWEAVE_ID_LENGTH: usize = 32;
+
+const BASE32_ALPHABET: &[u8] = b"abcdefghijklmnopqrstuvwxyz234567";
+const BASE36_ALPHABET: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+const BASE64URL_ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ContenthashError {
+    UnsupportedScheme,
+    UnsupportedNamespace(u64),
+    InvalidEncoding,
+    InvalidCid,
+    InvalidArweaveId,
+}
+
+impl fmt::Display for ContenthashError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ContenthashError::UnsupportedScheme => write!(f, "unsupported uri scheme, expected ipfs://, ipns:// or ar://"),
+            ContenthashError::UnsupportedNamespace(code) => write!(f, "unsupported contenthash namespace {:#x}", code),
+            ContenthashError::InvalidEncoding => write!(f, "invalid multibase encoding"),
+            ContenthashError::InvalidCid => write!(f, "invalid cid"),
+            ContenthashError::InvalidArweaveId => write!(f, "invalid arweave transaction id"),
+        }
+    }
+}
+
+impl std::error::Error for ContenthashError {}
+
+/// Converts `ipfs://CID`, `ipns://CID` or `ar://TX_ID` into the EIP-1577 bytes stored by the resolver
+/// `set_contenthash`: the namespace varint followed by a CIDv1 or by the 32 byte Arweave transaction id.
+/// CIDv0 (`Qm...`) and base58 peer ids are converted into CIDv1.
+pub fn encode_contenthash(uri: &str) -> Result<Vec<u8>, ContenthashError> {
+    let (namespace, payload) = if let Some(cid) = uri.strip_prefix("ipfs://") {
+        (IPFS_NAMESPACE, parse_cid(cid, DAG_PB_CODEC)?)
+    } else if let Some(cid) = uri.strip_prefix("ipns://") {
+        (IPNS_NAMESPACE, parse_cid(cid, LIBP2P_KEY_CODEC)?)
+    } else if let Some(id) = uri.strip_prefix("ar://") {
+        let id = base64url_decode(id).ok_or(ContenthashError::InvalidArweaveId)?;
+        if id.len() != ARWEAVE_ID_LENGTH {
+            return Err(ContenthashError::InvalidArweaveId);
+        }
+        (ARWEAVE_NAMESPACE, id)
+    } else {
+        return Err(ContenthashError::UnsupportedScheme);
+    };
+    let mut bytes = encode_varint(namespace);
+    bytes.extend(payload);
+    Ok(bytes)
+}
+
+/// Reverse of [encode_contenthash], IPFS CIDs are returned in base32 and IPNS names in base36 as the
+/// gateways expect them
+pub fn decode_contenthash(bytes: &[u8]) -> Result<String, ContenthashError> {
+    let (namespace, payload) = decode_varint(bytes).ok_or(ContenthashError::InvalidEncoding)?;
+    match namespace {
+        IPFS_NAMESPACE => {
+            validate_cid(payload)?;
+            Ok(format!("ipfs://b{}", base32_encode(payload)))
+        }
+        IPNS_NAMESPACE => {
+            validate_cid(payload)?;
+            Ok(format!("ipns://k{}", base_x_encode(payload, BASE36_ALPHABET)))
+        }
+        ARWEAVE_NAMESPACE => {
+            if payload.len() != ARWEAVE_ID_LENGTH {
+                return Err(ContenthashError::InvalidArweaveId);
+            }
+            Ok(format!("ar://{}", base64url_encode(payload)))
+        }
+        _ => Err(ContenthashError::UnsupportedNamespace(namespace)),
+    }
+}
+
+/// Parses a multibase CIDv1, or a bare base58 multihash (CIDv0 / peer id) which is wrapped with `codec`
+fn parse_cid(value: &str, codec: u64) -> Result<Vec<u8>, ContenthashError> {
+    let cid = if value.starts_with("Qm") || value.starts_with("12D3Koo") {
+        let multihash = base58_decode(value).map_err(|_| ContenthashError::InvalidEncoding)?;
+        let mut cid = encode_varint(CID_VERSION);
+        cid.extend(encode_varint(codec));
+        cid.extend(multihash);
+        cid
+    } else {
+        let mut characters = value.chars();
+        let decoded = match characters.next() {
+            Some('b') => base32_decode(characters.as_str()),
+            Some('k') => base_x_decode(characters.as_str(), BASE36_ALPHABET),
+            Some('z') => base58_decode(characters.as_str()).ok(),
+            _ => None,
+        };
+        decoded.ok_or(ContenthashError::InvalidEncoding)?
+    };
+    validate_cid(&cid)?;
+    Ok(cid)
+}
+
+/// Checks `<version><codec><multihash code><digest length><digest>` of a CIDv1
+fn validate_cid(cid: &[u8]) -> Result<(), ContenthashError> {
+    let (version, rest) = decode_varint(cid).ok_or(ContenthashError::InvalidCid)?;
+    let (_codec, rest) = decode_varint(rest).ok_or(ContenthashError::InvalidCid)?;
+    let (_hash_code, rest) = decode_varint(rest).ok_or(ContenthashError::InvalidCid)?;
+    let (digest_length, digest) = decode_varint(rest).ok_or(ContenthashError::InvalidCid)?;
+    if version != CID_VERSION || digest.len() as u64 != digest_length {
+        return Err(ContenthashError::InvalidCid);
+    }
+    Ok(())
+}
+
+/// Unsigned LEB128 as used by multiformats
+fn encode_varint(mut value: u64) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    while value >= 0x80 {
+        bytes.push((value as u8 & 0x7f) | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+    bytes
+}
+
+/// Returns the value and the remaining bytes
+fn decode_varint(bytes: &[u8]) -> Option<(u64, &[u8])> {
+    let mut value: u64 = 0;
+    for (i, &byte) in bytes.iter().enumerate().take(9) {
+        value |= ((byte & 0x7f) as u64) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Some((value, &bytes[i + 1..]));
+        }
+    }
+    None
+}
+
+/// Unpadded RFC 4648 encoding for alphabets of `2^bits` symbols
+fn bits_encode(bytes: &[u8], alphabet: &[u8], bits: u32) -> String {
+    let mask = (1 << bits) - 1;
+    let mut accumulator: u32 = 0;
+    let mut pending: u32 = 0;
+    let mut encoded = String::new();
+    for &byte in bytes {
+        accumulator = ((accumulator << 8) | byte as u32) & 0xffff;
+        pending += 8;
+        while pending >= bits {
+            pending -= bits;
+            encoded.push(alphabet[((accumulator >> pending) & mask) as usize] as char);
+        }
+    }
+    if pending > 0 {
+        encoded.push(alphabet[((accumulator << (bits - pending)) & mask) as usize] as char);
+    }
+    encoded
+}
+
+fn bits_decode(value: &str, alphabet: &[u8], bits: u32) -> Option<Vec<u8>> {
+    let mut accumulator: u32 = 0;
+    let mut pending: u32 = 0;
+    let mut decoded = Vec::new();
+    for character in value.bytes() {
+        let symbol = alphabet.iter().position(|&symbol| symbol == character)? as u32;
+        accumulator = ((accumulator << bits) | symbol) & 0xffff;
+        pending += bits;
+        if pending >= 8 {
+            pending -= 8;
+            decoded.push((accumulator >> pending) as u8);
+        }
+    }
+    // the padding bits must be zero
+    if accumulator & ((1 << pending) - 1) != 0 {
+        return None;
+    }
+    Some(decoded)
+}
+
+fn base32_encode(bytes: &[u8]) -> String {
+    bits_encode(bytes, BASE32_ALPHABET, 5)
+}
+
+fn base32_decode(value: &str) -> Option<Vec<u8>> {
+    bits_decode(value, BASE32_ALPHABET, 5)
+}
+
+fn base64url_encode(bytes: &[u8]) -> String {
+    bits_encode(bytes, BASE64URL_ALPHABET, 6)
+}
+
+fn base64url_decode(value: &str) -> Option<Vec<u8>> {
+    bits_decode(value, BASE64URL_ALPHABET, 6)
+}
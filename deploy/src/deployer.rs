@@ -1,4 +1,5 @@
 use fuels::core::Configurables;
+use fuels::types::Identity;
 use fuels::prelude::{
     abigen,
     Contract,
@@ -18,6 +19,7 @@ pub enum ContractType {
     Registrar,
     Registry,
     Resolver,
+    SubdomainRegistrar,
 }
 
 impl ContractType {
@@ -26,6 +28,7 @@ impl ContractType {
             ContractType::Registrar => "registrar",
             ContractType::Registry => "registry",
             ContractType::Resolver => "resolver",
+            ContractType::SubdomainRegistrar => "subdomain_registrar",
         }
     }
 }
@@ -43,18 +46,48 @@ abigen!(
         name = "Resolver",
         abi = "resolver/out/debug/resolver-abi.json"
     ),
+    Contract(
+        name = "SubdomainRegistrar",
+        abi = "subdomain_registrar/out/debug/subdomain_registrar-abi.json"
+    ),
     Contract(
         name = "Proxy",
         abi = "proxy/out/debug/proxy-abi.json"
     ),
 );
 
+/// Role granted to an account once the contracts are deployed
+#[derive(Debug, Clone)]
+pub struct RoleAssignment {
+    pub role: Role,
+    pub account: Identity,
+}
+
+/// Contracts which check the role
+fn role_contracts(role: &Role) -> Vec<ContractType> {
+    match role {
+        Role::PricingAdmin | Role::Treasurer => vec![ContractType::Registrar],
+        Role::TldAdmin => vec![ContractType::Registrar, ContractType::Registry],
+        Role::Pauser => vec![ContractType::Registrar, ContractType::Registry],
+    }
+}
+
+/// Registrar owning a top level domain other than `.fuel`
+#[derive(Debug, Clone)]
+pub struct TldRegistrarParams {
+    pub root_domain: String,
+    pub fees: Fees,
+    /// proxy of the registrar deployed for the domain before, upgraded instead of deploying a new one
+    pub proxy: Option<ContractId>,
+}
 
 #[derive(Debug, Clone)]
 pub struct ProxiesInfo {
     pub registrar: ContractId,
     pub registry: ContractId,
     pub resolver: ContractId,
+    /// deployed from scratch when missing
+    pub subdomain_registrar: Option<ContractId>,
 }
 
 impl ProxiesInfo {
@@ -63,6 +96,7 @@ impl ProxiesInfo {
             ContractType::Registrar => self.registrar,
             ContractType::Registry => self.registry,
             ContractType::Resolver => self.resolver,
+            ContractType::SubdomainRegistrar => self.subdomain_registrar.expect("missing subdomain registrar proxy"),
         }
     }
 }
@@ -92,6 +126,7 @@ pub struct LocalDeployParams {
     pub deployer_wallet: WalletUnlocked,
     pub user_wallet: WalletUnlocked,
     pub deploy_params: DeployParams,
+    pub initial_roles: Vec<RoleAssignment>,
 }
 
 pub enum DeployTarget {
@@ -100,9 +135,9 @@ pub enum DeployTarget {
 }
 
 pub async fn deploy(target: DeployTarget) -> Fixture {
-    let (deployer_wallet, user_wallet, params) = match target {
+    let (deployer_wallet, user_wallet, params, roles, extra_tld, grant_owner_roles) = match target {
         DeployTarget::Local(p) => {
-            (p.deployer_wallet, p.user_wallet, p.deploy_params)
+            (p.deployer_wallet, p.user_wallet, p.deploy_params, p.initial_roles, None, false)
         }
         DeployTarget::OnChain => {
             let config = config();
@@ -110,7 +145,7 @@ pub async fn deploy(target: DeployTarget) -> Fixture {
             let (deployer, user) = get_wallets(&config).await;
             println!("Deployer wallet address: {:?}", deployer.address().hash);
             println!("Deploy params: {:#?}", params);
-            (deployer, user, params)
+            (deployer, user, params, config.initial_roles, config.extra_tld, config.grant_owner_roles)
         }
     };
     let registry = deploy_registry_contract(&deployer_wallet, &params).await;
@@ -120,20 +155,113 @@ pub async fn deploy(target: DeployTarget) -> Fixture {
         &params,
         &registry,
         &resolver,
+        None,
+    ).await;
+    let subdomain_registrar = deploy_subdomain_registrar_contract(
+        &deployer_wallet,
+        &params,
+        &registry,
+        &resolver,
     ).await;
     let contracts = hashmap! {
         ContractType::Registry => registry.clone(),
         ContractType::Resolver => resolver.clone(),
         ContractType::Registrar => registrar.clone(),
+        ContractType::SubdomainRegistrar => subdomain_registrar,
     };
     println!("{:#?}", contracts);
-    Fixture {
+    let fixture = Fixture {
         deployer: deployer_wallet.clone(),
         user: user_wallet,
         registry_contract: Registry::new(registry.proxy_id, deployer_wallet.clone()),
         resolver_contract: Resolver::new(resolver.proxy_id, deployer_wallet.clone()),
         registrar_contract: Registrar::new(registrar.proxy_id, deployer_wallet),
         contracts,
+    };
+    assign_roles(&fixture, grant_owner_roles, roles.clone()).await;
+    trust_default_resolver(&fixture, resolver.proxy_id).await;
+    if let Some(tld) = extra_tld {
+        let tld_fixture = deploy_tld_registrar(&fixture, &tld).await;
+        assign_roles(&tld_fixture, false, roles).await;
+    }
+    fixture
+}
+
+/// Deploys one more registrar behind its own proxy and registers `params.root_domain` for it, or upgrades the
+/// registrar behind `params.proxy`. The returned fixture shares registry and resolver with `fixture` but mints through
+/// the registrar of the domain.
+pub async fn deploy_tld_registrar(fixture: &Fixture, params: &TldRegistrarParams) -> Fixture {
+    let registry = fixture.contracts.get(&ContractType::Registry).unwrap().clone();
+    let resolver = fixture.contracts.get(&ContractType::Resolver).unwrap().clone();
+    let subdomain_registrar = fixture.contracts.get(&ContractType::SubdomainRegistrar).unwrap().clone();
+    let deploy_params = match params.proxy {
+        Some(proxy) => DeployParams::Upgrade(ProxiesInfo {
+            registrar: proxy,
+            registry: registry.proxy_id,
+            resolver: resolver.proxy_id,
+            subdomain_registrar: Some(subdomain_registrar.proxy_id),
+        }),
+        None => {
+            let asset = fixture.get_domain_asset_id(&params.root_domain).await;
+            assert!(
+                !fixture.domain_exists(asset).await,
+                "{} is already registered, set EXTRA_TLD_REGISTRAR_PROXY to upgrade its registrar",
+                params.root_domain,
+            );
+            DeployParams::InitialDeploy
+        }
+    };
+    let registrar = deploy_registrar_contract(
+        &fixture.deployer,
+        &deploy_params,
+        &registry,
+        &resolver,
+        Some(params),
+    ).await;
+    println!("{} registrar: {:#?}", params.root_domain, registrar);
+    let contracts = hashmap! {
+        ContractType::Registry => registry,
+        ContractType::Resolver => resolver,
+        ContractType::Registrar => registrar.clone(),
+        ContractType::SubdomainRegistrar => subdomain_registrar,
+    };
+    Fixture {
+        deployer: fixture.deployer.clone(),
+        user: fixture.user.clone(),
+        registry_contract: fixture.registry_contract.clone(),
+        resolver_contract: fixture.resolver_contract.clone(),
+        registrar_contract: Registrar::new(registrar.proxy_id, fixture.deployer.clone()),
+        contracts,
+    }
+}
+
+/// Grants `roles`, and all roles to the deployer when `grant_owner_roles` is set. The owner receives all roles in
+/// initialize(), which isn't called through the proxy on upgrades, so the flag is only needed once when upgrading
+/// contracts deployed before roles existed; re-granting on every upgrade would undo intentional revocations.
+async fn assign_roles(fixture: &Fixture, grant_owner_roles: bool, mut roles: Vec<RoleAssignment>) {
+    if grant_owner_roles {
+        let deployer = Identity::Address(fixture.deployer.address().into());
+        for role in [Role::PricingAdmin, Role::Treasurer, Role::TldAdmin, Role::Pauser] {
+            roles.push(RoleAssignment { role, account: deployer.clone() });
+        }
+    }
+    for assignment in roles {
+        for contract in role_contracts(&assignment.role) {
+            fixture.grant_role(&contract, assignment.role.clone(), assignment.account.clone()).await;
+            println!("Granted {:?} on {} to {:?}", assignment.role, contract.name(), assignment.account);
+        }
+    }
+}
+
+/// The registry asks only trusted resolvers for operators. Trusting needs the registry owner, otherwise the
+/// resolver is printed for the owner to trust it.
+async fn trust_default_resolver(fixture: &Fixture, resolver: ContractId) {
+    if fixture.is_trusted_resolver(resolver).await {
+        return;
+    }
+    match fixture.set_trusted_resolver(resolver, true).await {
+        Ok(()) => println!("Trusted resolver {:?}", resolver),
+        Err(error) => println!("Couldn't trust resolver {:?}, the registry owner must call set_trusted_resolver: {}", resolver, error),
     }
 }
 
@@ -248,13 +376,19 @@ async fn deploy_registrar_contract(
     deploy_params: &DeployParams,
     registry: &DeployResult,
     resolver: &DeployResult,
+    tld: Option<&TldRegistrarParams>,
 ) -> DeployResult {
     println!("Deploying registrar contract...");
-    let configurables = RegistrarConfigurables::default()
+    let mut configurables = RegistrarConfigurables::default()
         .with_REGISTRY_CONTRACT_ID(registry.proxy_id)
         .unwrap()
         .with_DEFAULT_RESOLVER_CONTRACT_ID(resolver.proxy_id)
         .unwrap();
+    if let Some(tld) = tld {
+        configurables = configurables.with_ETH_FEES(tld.fees.clone()).unwrap();
+    }
+    // the registrar falls back to `.fuel` until a root domain is set
+    let root_domain = tld.map(|tld| tld.root_domain.as_str());
     let init = |registrar: DeployResult| async move {
         // call target initialize() directly so no one else can set the owner
         let owner = Registrar::new(registrar.target_id, wallet.clone())
@@ -276,9 +410,21 @@ async fn deploy_registrar_contract(
                 .unwrap()
                 .value;
             println!("Registrar owner (called through proxy): {:?}", owner);
+            if let Some(root_domain) = root_domain {
+                registrar_contract.methods()
+                    .set_root_domain(root_domain.to_string())
+                    .with_contract_ids(&[
+                        registrar.target_id.into(),
+                        registry.proxy_id.into(),
+                        registry.target_id.into(),
+                    ])
+                    .call()
+                    .await
+                    .unwrap();
+            }
             let high_level_domain_asset = registry_contract
                 .methods()
-                .register_high_level_domain(registrar.proxy_id.into(), "fuel".to_string())
+                .register_high_level_domain(registrar.proxy_id.into(), root_domain.unwrap_or("fuel").to_string())
                 .with_contract_ids(&[
                     registry.target_id.into(),
                     registrar.proxy_id.into(),
@@ -299,6 +445,32 @@ async fn deploy_registrar_contract(
     ).await
 }
 
+async fn deploy_subdomain_registrar_contract(
+    wallet: &WalletUnlocked,
+    deploy_params: &DeployParams,
+    registry: &DeployResult,
+    resolver: &DeployResult,
+) -> DeployResult {
+    println!("Deploying subdomain registrar contract...");
+    // the subdomain registrar is newer than the other contracts, so an upgrade may need to deploy it first
+    let deploy_params = match deploy_params {
+        DeployParams::Upgrade(proxies) if proxies.subdomain_registrar.is_none() => &DeployParams::InitialDeploy,
+        _ => deploy_params,
+    };
+    let configurables = SubdomainRegistrarConfigurables::default()
+        .with_REGISTRY_CONTRACT_ID(registry.proxy_id)
+        .unwrap()
+        .with_DEFAULT_RESOLVER_CONTRACT_ID(resolver.proxy_id)
+        .unwrap();
+    deploy_with_proxy(
+        wallet,
+        &ContractType::SubdomainRegistrar,
+        Some(configurables.into()),
+        deploy_params,
+        skip_init,
+    ).await
+}
+
 async fn deploy_proxy_contract(
     wallet: &WalletUnlocked,
     proxy_target: ContractId,
@@ -1,9 +1,42 @@
 use fuels::{accounts::wallet::WalletUnlocked, prelude::*};
 use std::collections::HashMap;
 
-use crate::deployer::{ContractType, DeployResult, Metadata, Registrar, Registry, Resolver};
+use crate::address_codec::{decode_address, encode_address};
+use crate::contenthash::{decode_contenthash, encode_contenthash};
+use crate::interfaces::{interface_id, ADDR_RESOLVER, CONTENTHASH_RESOLVER, INTERFACE_SUPPORT, MULTICALL_RESOLVER, TEXT_RESOLVER};
+use crate::deployer::{Beneficiary, ContractType, DeployResult, DiscountVoucher, Metadata, PausableOperation, PeriodLimits, RecordUpdate, Registrar, Registry, Resolver, Role, SubdomainOptions, SubdomainRegistrar, SubdomainTerms};
+use crate::premium::{premium_schedule, PremiumConfig};
+use crate::voucher::sign_voucher;
+use fuels::crypto::SecretKey;
+use fuels::programs::calls::CallHandler;
 use chrono::Duration;
-use fuels::types::Identity;
+use fuels::types::{Bits256, Bytes, Identity};
+use fuels::types::errors::transaction::Reason;
+
+/// Largest page returned by the registry `get_subdomains`
+pub const MAX_SUBDOMAINS_PAGE_SIZE: u64 = 50;
+
+/// Text record keys read by [Fixture::get_text_records]
+pub const STANDARD_TEXT_KEYS: [&str; 6] = ["avatar", "url", "email", "com.twitter", "com.github", "description"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TreasuryReport {
+    pub collected_revenue: u64,
+    pub withdrawn_funds: u64,
+    pub pending_funds: u64,
+    pub pending_referral_fees: u64,
+}
+
+/// State of a domain after [Fixture::register_full]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegistrationState {
+    pub asset_id: AssetId,
+    pub domain_name: String,
+    pub expiration: Option<u64>,
+    pub resolver: Option<ContractId>,
+    pub resolved_address: Option<Identity>,
+    pub primary_domain: Option<AssetId>,
+}
 
 pub struct Fixture {
     pub deployer: WalletUnlocked,
@@ -27,6 +60,14 @@ impl Fixture {
         self.contracts.get(&ContractType::Resolver).unwrap().clone()
     }
 
+    fn subdomain_registrar(&self) -> DeployResult {
+        self.contracts.get(&ContractType::SubdomainRegistrar).expect("subdomain registrar is not deployed").clone()
+    }
+
+    fn subdomain_registrar_contract(&self, wallet: &WalletUnlocked) -> SubdomainRegistrar<WalletUnlocked> {
+        SubdomainRegistrar::new(self.subdomain_registrar().proxy_id, wallet.clone())
+    }
+
     pub async fn mint_domain(
         &self,
         domain: &str,
@@ -58,6 +99,12 @@ impl Fixture {
             .value
     }
 
+    /// Whether `owner` can attach the NFT to prove ownership. Helpers only attach it when held, so that calls of
+    /// other accounts reach the contract and fail its ownership check instead of failing to build the transaction.
+    async fn holds_asset(&self, owner: &WalletUnlocked, asset: AssetId) -> bool {
+        owner.get_asset_balance(&asset).await.unwrap() > 0
+    }
+
     pub async fn get_domain_name(&self, asset: AssetId) -> String {
         self.registry_contract
             .methods()
@@ -104,6 +151,56 @@ impl Fixture {
             .unwrap();
     }
 
+    /// Sets the resolver as `owner`, who proves control by presenting the NFT of `proof_domain`,
+    /// e.g. the parent of a controlled subdomain. Without the NFT the registry accepts operators of the default resolver
+    pub async fn set_domain_resolver_with_proof(
+        &self,
+        owner: &WalletUnlocked,
+        domain: &str,
+        proof_domain: &str,
+        resolver: ContractId,
+    ) -> Result<()> {
+        let proof_asset = self.get_domain_asset_id(proof_domain).await;
+        let mut call = self.registry_contract
+            .clone()
+            .with_account(owner.clone())
+            .methods()
+            .set_resolver(domain.to_string(), resolver)
+            .with_contract_ids(&[
+                self.registry().target_id.into(),
+                self.resolver().proxy_id.into(),
+                self.resolver().target_id.into(),
+            ]);
+        if self.holds_asset(owner, proof_asset).await {
+            call = call.add_custom_asset(proof_asset, 1, Some(owner.address().into()));
+        }
+        call.call()
+            .await
+            .map(|response| response.value)
+    }
+
+    /// Called by the registry owner, untrusted resolvers are no longer accepted nor called on resolution
+    pub async fn set_trusted_resolver(&self, resolver: ContractId, trusted: bool) -> Result<()> {
+        self.registry_contract
+            .methods()
+            .set_trusted_resolver(resolver, trusted)
+            .with_contract_ids(&[self.registry().target_id.into()])
+            .call()
+            .await
+            .map(|response| response.value)
+    }
+
+    pub async fn is_trusted_resolver(&self, resolver: ContractId) -> bool {
+        self.registry_contract
+            .methods()
+            .is_trusted_resolver(resolver)
+            .with_contract_ids(&[self.registry().target_id.into()])
+            .simulate(Execution::StateReadOnly)
+            .await
+            .unwrap()
+            .value
+    }
+
     pub async fn get_domain_resolver(&self, domain: &str) -> Option<ContractId> {
         self.registry_contract
             .methods()
@@ -135,7 +232,11 @@ impl Fixture {
         self.registrar_contract
             .methods()
             .domain_price(domain.to_string(), years, *asset)
-            .with_contract_ids(&[self.registrar().target_id.into()])
+            .with_contract_ids(&[
+                self.registrar().target_id.into(),
+                self.registry().proxy_id.into(),
+                self.registry().target_id.into(),
+            ])
             .simulate(Execution::StateReadOnly)
             .await
             .unwrap()
@@ -156,158 +257,181 @@ impl Fixture {
             .value
     }
 
-    pub async fn _mint_domain(
-        &self,
-        domain: &str,
-        years: u64,
-        fee_to_transfer: u64,
-        asset: Option<AssetId>,
-    ) -> Result<AssetId> {
-        let tx_policies = TxPolicies::default()
-            .with_script_gas_limit(1_000_000);
+    pub async fn set_expired_premium(&self, asset: &AssetId, start_premium: u64) {
         self.registrar_contract
-            .clone()
-            .with_account(self.user.clone())
             .methods()
-            .mint_domain(domain.to_string(), years)
-            .with_variable_output_policy(VariableOutputPolicy::Exactly(1))
-            .with_tx_policies(tx_policies)
-            .call_params(
-                CallParameters::default()
-                    .with_amount(fee_to_transfer)
-                    .with_asset_id(asset.unwrap_or(AssetId::BASE)),
-            )
-            .unwrap()
-            .with_contract_ids(&[
-                self.registrar().target_id.into(),
-                self.registry().proxy_id.into(),
-                self.registry().target_id.into()
-            ])
+            .set_expired_premium(*asset, start_premium)
+            .with_contract_ids(&[self.registrar().target_id.into()])
             .call()
             .await
-            .map(|response| response.value)
+            .unwrap();
     }
 
-    pub async fn resolve_domain(&self, domain: &str) -> Option<Identity> {
-        let resolver: ContractId = self
-            .registry_contract
+    pub async fn get_expired_premium(&self, asset: &AssetId) -> u64 {
+        self.registrar_contract
             .methods()
-            .get_resolver(domain.to_string())
-            .with_contract_ids(&[self.registry().target_id.into()])
+            .get_expired_premium(*asset)
+            .with_contract_ids(&[self.registrar().target_id.into()])
             .simulate(Execution::StateReadOnly)
             .await
             .unwrap()
             .value
-            .unwrap();
-        assert_eq!(resolver, self.resolver_contract.id().clone().into());
-        let asset_id: AssetId = self
-            .registry_contract
+    }
+
+    pub async fn set_premium_decay_period(&self, duration: u64) {
+        self.registrar_contract
             .methods()
-            .get_domain_asset_id(domain.to_string())
-            .with_contract_ids(&[self.registry().target_id.into()])
-            .simulate(Execution::StateReadOnly)
+            .set_premium_decay_period(duration)
+            .with_contract_ids(&[self.registrar().target_id.into()])
+            .call()
             .await
-            .unwrap()
-            .value;
-        let resolved: Option<Identity> = self
-            .resolver_contract
+            .unwrap();
+    }
+
+    pub async fn get_premium_decay_period(&self) -> u64 {
+        self.registrar_contract
             .methods()
-            .resolve(asset_id)
-            .with_contract_ids(&[self.resolver().target_id.into()])
+            .get_premium_decay_period()
+            .with_contract_ids(&[self.registrar().target_id.into()])
             .simulate(Execution::StateReadOnly)
             .await
             .unwrap()
-            .value;
-        resolved
+            .value
     }
 
-    pub async fn reverse_resolve_domain(&self, identity: Identity) -> Option<AssetId> {
-        let resolved: Option<AssetId> = self
-            .registry_contract
+    pub async fn get_expired_domain_premium(&self, domain: &str, asset: &AssetId) -> u64 {
+        self.registrar_contract
             .methods()
-            .resolve_to_primary_domain(identity)
+            .expired_domain_premium(domain.to_string(), *asset)
             .with_contract_ids(&[
+                self.registrar().target_id.into(),
+                self.registry().proxy_id.into(),
                 self.registry().target_id.into(),
-                self.resolver().proxy_id.into(),
-                self.resolver().target_id.into(),
             ])
-            .call()
+            .simulate(Execution::StateReadOnly)
             .await
             .unwrap()
-            .value;
-        resolved
+            .value
     }
 
-    pub async fn set_resolution(&self, domain: &str, to: Option<Identity>) {
-        let asset_id: AssetId = self
+    /// Previews the premium in `fee_asset` of a released domain of the registrar over time, sampled every `step`
+    /// seconds, with the start premium and decay period configured in the registrar.
+    /// Returns an empty schedule if the domain was never minted.
+    pub async fn preview_expired_premium(
+        &self,
+        domain: &str,
+        fee_asset: &AssetId,
+        step: u64,
+    ) -> Vec<(u64, u64)> {
+        let full_domain = self.full_domain_name(domain).await;
+        let expiration = match self.get_domain_expiration(&full_domain).await {
+            Some(exp) => exp,
+            None => return vec![],
+        };
+        let grace_period: u64 = self
             .registry_contract
             .methods()
-            .get_domain_asset_id(domain.to_string())
-            .with_contract_ids(&[
-                self.registry().target_id.into(),
-                self.resolver().proxy_id.into(),
-                self.resolver().target_id.into(),
-            ])
+            .get_grace_period(full_domain)
+            .with_contract_ids(&[self.registry().target_id.into()])
             .simulate(Execution::StateReadOnly)
             .await
             .unwrap()
-            .value;
-        self.resolver_contract
+            .value
+            .unwrap_or(0);
+        let config = PremiumConfig {
+            start_premium: self.get_expired_premium(fee_asset).await,
+            decay_period: self.get_premium_decay_period().await,
+        };
+        premium_schedule(config, expiration + grace_period, step)
+    }
+
+    /// Mints all domains for the user in a single call, either all of them are minted or none.
+    pub async fn mint_domains(
+        &self,
+        domains: &[(&str, u64)],
+        fee_to_transfer: u64,
+    ) -> Result<Vec<AssetId>> {
+        let tx_policies = TxPolicies::default()
+            .with_script_gas_limit(1_000_000 * domains.len().max(1) as u64);
+        let domains = domains
+            .iter()
+            .map(|(domain, years)| (domain.to_string(), *years))
+            .collect::<Vec<_>>();
+        // one output per minted NFT and one for a possible overpayment refund
+        let variable_outputs = domains.len() + 1;
+        self.registrar_contract
             .clone()
             .with_account(self.user.clone())
             .methods()
-            .set(asset_id, to)
+            .mint_domains(domains)
+            .with_variable_output_policy(VariableOutputPolicy::Exactly(variable_outputs))
+            .with_tx_policies(tx_policies)
+            .call_params(
+                CallParameters::default()
+                    .with_amount(fee_to_transfer)
+                    .with_asset_id(AssetId::BASE),
+            )
+            .unwrap()
             .with_contract_ids(&[
+                self.registrar().target_id.into(),
                 self.registry().proxy_id.into(),
-                self.registry().target_id.into(),
-                self.resolver().target_id.into()
+                self.registry().target_id.into()
             ])
-            .add_custom_asset(asset_id, 1, Some(self.user.address().into()))
             .call()
             .await
-            .unwrap();
+            .map(|response| response.value)
     }
 
-    pub async fn set_primary(&self, domain: &str) {
-        let asset_id: AssetId = self
-            .registry_contract
-            .methods()
-            .get_domain_asset_id(domain.to_string())
-            .with_contract_ids(&[self.registry().target_id.into()])
-            .simulate(Execution::StateReadOnly)
-            .await
-            .unwrap()
-            .value;
-        self.registry_contract
+    /// Sum of the prices of all domains, the amount expected by [Fixture::mint_domains].
+    pub async fn get_batch_price(&self, domains: &[(&str, u64)], asset: &AssetId) -> u64 {
+        let mut total = 0;
+        for (domain, years) in domains {
+            total += self.get_domain_price(domain, *years, asset).await;
+        }
+        total
+    }
+
+    /// Mints the domain to `recipient` (paid by the user) and optionally sets its resolved address.
+    pub async fn mint_domain_for(
+        &self,
+        recipient: Identity,
+        domain: &str,
+        years: u64,
+        fee_to_transfer: u64,
+        resolve_to: Option<Identity>,
+    ) -> Result<AssetId> {
+        let tx_policies = TxPolicies::default()
+            .with_script_gas_limit(1_000_000);
+        self.registrar_contract
             .clone()
             .with_account(self.user.clone())
             .methods()
-            .set_primary(asset_id)
+            .mint_domain_for(recipient, domain.to_string(), years, resolve_to)
+            // one output for the minted (or forwarded) NFT and one for a possible overpayment refund
+            .with_variable_output_policy(VariableOutputPolicy::Exactly(2))
+            .with_tx_policies(tx_policies)
+            .call_params(
+                CallParameters::default()
+                    .with_amount(fee_to_transfer)
+                    .with_asset_id(AssetId::BASE),
+            )
+            .unwrap()
             .with_contract_ids(&[
+                self.registrar().target_id.into(),
+                self.registry().proxy_id.into(),
                 self.registry().target_id.into(),
                 self.resolver().proxy_id.into(),
                 self.resolver().target_id.into(),
             ])
             .call()
             .await
-            .unwrap();
-    }
-
-    pub async fn withdraw_funds(&self, asset_id: &AssetId) {
-        self.registrar_contract
-            .methods()
-            .withdraw_funds(*asset_id)
-            .with_contract_ids(&[self.registrar().target_id.into()])
-            .with_variable_output_policy(VariableOutputPolicy::Exactly(1))
-            .call()
-            .await
-            .unwrap();
+            .map(|response| response.value)
     }
 
-    pub async fn get_total_assets(&self) -> u64 {
+    pub async fn get_next_domain_asset_id(&self, domain: &str) -> AssetId {
         self.registry_contract
             .methods()
-            .total_assets()
+            .get_next_domain_asset_id(domain.to_string())
             .with_contract_ids(&[self.registry().target_id.into()])
             .simulate(Execution::StateReadOnly)
             .await
@@ -315,31 +439,1269 @@ impl Fixture {
             .value
     }
 
-    pub async fn transfer(&self, owner: &WalletUnlocked, domain: &str, to: &Bech32Address) {
-        let asset_id: AssetId = self.get_domain_asset_id(domain).await;
-        owner
-            .transfer(to, 1, asset_id, TxPolicies::default())
-            .await
-            .unwrap();
-    }
-
-    pub async fn set_fees(
+    /// Mints the domain for the user, resolves it to the user and optionally makes it the user's primary domain,
+    /// all in a single transaction.
+    pub async fn register_full(
         &self,
-        asset: &AssetId,
-        three_letter_fee: u64,
-        four_letter_fee: u64,
-        long_domain_fee: u64,
-    ) {
-        self.registrar_contract
+        domain: &str,
+        years: u64,
+        fee_to_transfer: u64,
+        set_primary: bool,
+    ) -> Result<RegistrationState> {
+        let user_identity = Identity::Address(self.user.address().into());
+        let full_domain = self.full_domain_name(domain).await;
+        let tx_policies = TxPolicies::default()
+            .with_script_gas_limit(2_000_000);
+        let mint_call = self.registrar_contract
+            .clone()
+            .with_account(self.user.clone())
             .methods()
-            .set_fees(*asset, three_letter_fee, four_letter_fee, long_domain_fee)
-            .with_contract_ids(&[self.registrar().target_id.into()])
-            .call()
-            .await
-            .unwrap();
+            .mint_domain_for(user_identity.clone(), domain.to_string(), years, Some(user_identity.clone()))
+            .call_params(
+                CallParameters::default()
+                    .with_amount(fee_to_transfer)
+                    .with_asset_id(AssetId::BASE),
+            )
+            .unwrap()
+            .with_contract_ids(&[
+                self.registrar().target_id.into(),
+                self.registry().proxy_id.into(),
+                self.registry().target_id.into(),
+                self.resolver().proxy_id.into(),
+                self.resolver().target_id.into(),
+            ]);
+        let multi_call = CallHandler::new_multi_call(self.user.clone())
+            .add_call(mint_call)
+            // one output for the forwarded NFT and one for a possible overpayment refund
+            .with_variable_output_policy(VariableOutputPolicy::Exactly(2))
+            .with_tx_policies(tx_policies);
+        let asset_id = if set_primary {
+            // the asset doesn't exist yet, so its id is predicted from the domain generation
+            let next_asset_id = self.get_next_domain_asset_id(&full_domain).await;
+            let primary_call = self.registry_contract
+                .clone()
+                .with_account(self.user.clone())
+                .methods()
+                .set_primary(next_asset_id)
+                .with_contract_ids(&[
+                    self.registry().target_id.into(),
+                    self.resolver().proxy_id.into(),
+                    self.resolver().target_id.into(),
+                ]);
+            multi_call
+                .add_call(primary_call)
+                .call::<(AssetId, ())>()
+                .await?
+                .value
+                .0
+        } else {
+            multi_call
+                .call::<(AssetId,)>()
+                .await?
+                .value
+                .0
+        };
+        Ok(RegistrationState {
+            asset_id,
+            domain_name: self.get_domain_name(asset_id).await,
+            expiration: self.get_domain_expiration(&full_domain).await,
+            resolver: self.get_domain_resolver(&full_domain).await,
+            resolved_address: self.resolve_domain(&full_domain).await,
+            primary_domain: self.reverse_resolve_domain(user_identity).await,
+        })
     }
 
-    pub async fn set_grace_period(&self, duration: u64) {
+    pub async fn mint_domain_with_referrer(
+        &self,
+        domain: &str,
+        years: u64,
+        fee_to_transfer: u64,
+        referrer: Option<Identity>,
+        voucher: Option<DiscountVoucher>,
+    ) -> Result<AssetId> {
+        let tx_policies = TxPolicies::default()
+            .with_script_gas_limit(1_000_000);
+        self.registrar_contract
+            .clone()
+            .with_account(self.user.clone())
+            .methods()
+            .mint_domain_with_referrer(domain.to_string(), years, referrer, voucher)
+            // one output for the minted NFT and one for a possible overpayment refund
+            .with_variable_output_policy(VariableOutputPolicy::Exactly(2))
+            .with_tx_policies(tx_policies)
+            .call_params(
+                CallParameters::default()
+                    .with_amount(fee_to_transfer)
+                    .with_asset_id(AssetId::BASE),
+            )
+            .unwrap()
+            .with_contract_ids(&[
+                self.registrar().target_id.into(),
+                self.registry().proxy_id.into(),
+                self.registry().target_id.into()
+            ])
+            .call()
+            .await
+            .map(|response| response.value)
+    }
+
+    pub async fn get_domain_price_with_voucher(
+        &self,
+        domain: &str,
+        years: u64,
+        asset: &AssetId,
+        voucher: DiscountVoucher,
+    ) -> u64 {
+        // the voucher is checked against the sender, the same account as in `mint_domain_with_referrer`
+        self.registrar_contract
+            .clone()
+            .with_account(self.user.clone())
+            .methods()
+            .domain_price_with_voucher(domain.to_string(), years, *asset, voucher)
+            .with_contract_ids(&[
+                self.registrar().target_id.into(),
+                self.registry().proxy_id.into(),
+                self.registry().target_id.into(),
+            ])
+            .simulate(Execution::StateReadOnly)
+            .await
+            .unwrap()
+            .value
+    }
+
+    /// Signs a discount voucher bound to the deployed registrar, `buyer` and `domain`.
+    pub fn create_voucher(
+        &self,
+        signer: &SecretKey,
+        buyer: &Identity,
+        domain: &str,
+        id: [u8; 32],
+        discount_bps: u64,
+        expires_at: u64,
+    ) -> DiscountVoucher {
+        sign_voucher(signer, self.registrar().proxy_id, buyer, domain, id, discount_bps, expires_at)
+    }
+
+    pub async fn set_voucher_signer(&self, signer: Option<Address>) {
+        self.registrar_contract
+            .methods()
+            .set_voucher_signer(signer)
+            .with_contract_ids(&[self.registrar().target_id.into()])
+            .call()
+            .await
+            .unwrap();
+    }
+
+    pub async fn is_voucher_used(&self, id: [u8; 32]) -> bool {
+        self.registrar_contract
+            .methods()
+            .is_voucher_used(Bits256(id))
+            .with_contract_ids(&[self.registrar().target_id.into()])
+            .simulate(Execution::StateReadOnly)
+            .await
+            .unwrap()
+            .value
+    }
+
+    pub async fn set_referral_fee(&self, fee_bps: u64) {
+        self.registrar_contract
+            .methods()
+            .set_referral_fee(fee_bps)
+            .with_contract_ids(&[self.registrar().target_id.into()])
+            .call()
+            .await
+            .unwrap();
+    }
+
+    pub async fn get_referral_balance(&self, referrer: Identity, asset_id: &AssetId) -> u64 {
+        self.registrar_contract
+            .methods()
+            .get_referral_balance(referrer, *asset_id)
+            .with_contract_ids(&[self.registrar().target_id.into()])
+            .simulate(Execution::StateReadOnly)
+            .await
+            .unwrap()
+            .value
+    }
+
+    pub async fn withdraw_referral_fees(&self, referrer: &WalletUnlocked, asset_id: &AssetId) {
+        self.registrar_contract
+            .clone()
+            .with_account(referrer.clone())
+            .methods()
+            .withdraw_referral_fees(*asset_id)
+            .with_contract_ids(&[self.registrar().target_id.into()])
+            .with_variable_output_policy(VariableOutputPolicy::Exactly(1))
+            .call()
+            .await
+            .unwrap();
+    }
+
+    pub async fn _mint_domain(
+        &self,
+        domain: &str,
+        years: u64,
+        fee_to_transfer: u64,
+        asset: Option<AssetId>,
+    ) -> Result<AssetId> {
+        let tx_policies = TxPolicies::default()
+            .with_script_gas_limit(1_000_000);
+        self.registrar_contract
+            .clone()
+            .with_account(self.user.clone())
+            .methods()
+            .mint_domain(domain.to_string(), years)
+            // one output for the minted NFT and one for a possible overpayment refund
+            .with_variable_output_policy(VariableOutputPolicy::Exactly(2))
+            .with_tx_policies(tx_policies)
+            .call_params(
+                CallParameters::default()
+                    .with_amount(fee_to_transfer)
+                    .with_asset_id(asset.unwrap_or(AssetId::BASE)),
+            )
+            .unwrap()
+            .with_contract_ids(&[
+                self.registrar().target_id.into(),
+                self.registry().proxy_id.into(),
+                self.registry().target_id.into()
+            ])
+            .call()
+            .await
+            .map(|response| response.value)
+    }
+
+    /// Asset of a domain which uses the default resolver
+    async fn default_resolver_asset_id(&self, domain: &str) -> AssetId {
+        let resolver: ContractId = self
+            .registry_contract
+            .methods()
+            .get_resolver(domain.to_string())
+            .with_contract_ids(&[self.registry().target_id.into()])
+            .simulate(Execution::StateReadOnly)
+            .await
+            .unwrap()
+            .value
+            .unwrap();
+        assert_eq!(resolver, self.resolver_contract.id().clone().into());
+        self.registry_contract
+            .methods()
+            .get_domain_asset_id(domain.to_string())
+            .with_contract_ids(&[self.registry().target_id.into()])
+            .simulate(Execution::StateReadOnly)
+            .await
+            .unwrap()
+            .value
+    }
+
+    /// Resolves through `resolve_checked`, so domains which are not active resolve to `None`
+    pub async fn resolve_domain(&self, domain: &str) -> Option<Identity> {
+        let asset_id = self.default_resolver_asset_id(domain).await;
+        let resolved: Option<Identity> = self
+            .resolver_contract
+            .methods()
+            .resolve_checked(asset_id)
+            .with_contract_ids(&[
+                self.resolver().target_id.into(),
+                self.registry().proxy_id.into(),
+                self.registry().target_id.into(),
+            ])
+            .simulate(Execution::StateReadOnly)
+            .await
+            .unwrap()
+            .value;
+        resolved
+    }
+
+    /// The stored address regardless of the domain state
+    pub async fn resolve_domain_unchecked(&self, domain: &str) -> Option<Identity> {
+        let asset_id = self.default_resolver_asset_id(domain).await;
+        let resolved: Option<Identity> = self
+            .resolver_contract
+            .methods()
+            .resolve(asset_id)
+            .with_contract_ids(&[self.resolver().target_id.into()])
+            .simulate(Execution::StateReadOnly)
+            .await
+            .unwrap()
+            .value;
+        resolved
+    }
+
+    pub async fn reverse_resolve_domain(&self, identity: Identity) -> Option<AssetId> {
+        let resolved: Option<AssetId> = self
+            .registry_contract
+            .methods()
+            .resolve_to_primary_domain(identity)
+            .with_contract_ids(&[
+                self.registry().target_id.into(),
+                self.resolver().proxy_id.into(),
+                self.resolver().target_id.into(),
+            ])
+            .call()
+            .await
+            .unwrap()
+            .value;
+        resolved
+    }
+
+    pub async fn set_resolution(&self, domain: &str, to: Option<Identity>) {
+        let asset_id: AssetId = self
+            .registry_contract
+            .methods()
+            .get_domain_asset_id(domain.to_string())
+            .with_contract_ids(&[
+                self.registry().target_id.into(),
+                self.resolver().proxy_id.into(),
+                self.resolver().target_id.into(),
+            ])
+            .simulate(Execution::StateReadOnly)
+            .await
+            .unwrap()
+            .value;
+        self.resolver_contract
+            .clone()
+            .with_account(self.user.clone())
+            .methods()
+            .set(asset_id, to)
+            .with_contract_ids(&[
+                self.registry().proxy_id.into(),
+                self.registry().target_id.into(),
+                self.resolver().target_id.into()
+            ])
+            .add_custom_asset(asset_id, 1, Some(self.user.address().into()))
+            .call()
+            .await
+            .unwrap();
+    }
+
+    /// Sets or clears (`None`) a text record as the user, who must hold the domain NFT
+    pub async fn set_text(&self, domain: &str, key: &str, value: Option<&str>) -> Result<()> {
+        self.set_text_as(&self.user, domain, key, value).await
+    }
+
+    /// Sets a text record as `account`, with the domain NFT if it holds it and as an operator otherwise
+    pub async fn set_text_as(&self, account: &WalletUnlocked, domain: &str, key: &str, value: Option<&str>) -> Result<()> {
+        let (resolver, contract_ids) = self.get_domain_resolver_for_update(account, domain, TEXT_RESOLVER).await?;
+        let asset_id = self.get_domain_asset_id(domain).await;
+        let mut call = resolver
+            .methods()
+            .set_text(asset_id, key.to_string(), value.map(|value| value.to_string()))
+            .with_contract_ids(&contract_ids);
+        if self.holds_asset(account, asset_id).await {
+            call = call.add_custom_asset(asset_id, 1, Some(account.address().into()));
+        }
+        call.call()
+            .await
+            .map(|response| response.value)
+    }
+
+    /// Contracts to pass along with calls to `resolver`, the default one is behind a proxy
+    fn resolver_contract_ids(&self, resolver: ContractId) -> Vec<Bech32ContractId> {
+        if resolver == self.resolver().proxy_id {
+            vec![self.resolver().target_id.into()]
+        } else {
+            vec![]
+        }
+    }
+
+    /// Probes the resolver. Resolvers without `supports_interface` revert and support nothing, other errors
+    /// (e.g. of the provider) are returned.
+    pub async fn supports_interface(&self, resolver: ContractId, interface: &str) -> Result<bool> {
+        let result = Resolver::new(resolver, self.user.clone())
+            .methods()
+            .supports_interface(interface_id(interface))
+            .with_contract_ids(&self.resolver_contract_ids(resolver))
+            .simulate(Execution::StateReadOnly)
+            .await;
+        match result {
+            Ok(response) => Ok(response.value),
+            Err(Error::Transaction(Reason::Reverted { .. })) => Ok(false),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// The resolver of the domain if it supports `interface`
+    async fn get_domain_resolver_supporting(&self, domain: &str, interface: &str) -> Result<Option<(Resolver<WalletUnlocked>, Vec<Bech32ContractId>)>> {
+        let Some(resolver) = self.get_domain_resolver(domain).await else {
+            return Ok(None);
+        };
+        if !self.supports_interface(resolver, INTERFACE_SUPPORT).await? || !self.supports_interface(resolver, interface).await? {
+            return Ok(None);
+        }
+        Ok(Some((Resolver::new(resolver, self.user.clone()), self.resolver_contract_ids(resolver))))
+    }
+
+    /// The resolver of the domain to update records as `account`, fails if it doesn't support `interface`
+    async fn get_domain_resolver_for_update(
+        &self,
+        account: &WalletUnlocked,
+        domain: &str,
+        interface: &str,
+    ) -> Result<(Resolver<WalletUnlocked>, Vec<Bech32ContractId>)> {
+        let (resolver, mut contract_ids) = self
+            .get_domain_resolver_supporting(domain, interface)
+            .await?
+            .ok_or_else(|| Error::Other(format!("the resolver of {} doesn't support {}", domain, interface)))?;
+        contract_ids.extend([self.registry().proxy_id.into(), self.registry().target_id.into()]);
+        Ok((resolver.with_account(account.clone()), contract_ids))
+    }
+
+    async fn read_text(resolver: &Resolver<WalletUnlocked>, contract_ids: &[Bech32ContractId], asset_id: AssetId, key: &str) -> Option<String> {
+        resolver
+            .methods()
+            .text(asset_id, key.to_string())
+            .with_contract_ids(contract_ids)
+            .simulate(Execution::StateReadOnly)
+            .await
+            .unwrap()
+            .value
+    }
+
+    /// `None` as well if the domain resolver doesn't support text records
+    pub async fn get_text(&self, domain: &str, key: &str) -> Option<String> {
+        let (resolver, contract_ids) = self.get_domain_resolver_supporting(domain, TEXT_RESOLVER).await.unwrap()?;
+        let asset_id = self.get_domain_asset_id(domain).await;
+        Self::read_text(&resolver, &contract_ids, asset_id, key).await
+    }
+
+    /// Records of [STANDARD_TEXT_KEYS] and of `extra_keys` which are set for the domain. Keys can't be
+    /// enumerated on chain, so custom records are only returned when their key is passed in `extra_keys`.
+    pub async fn get_text_records(&self, domain: &str, extra_keys: &[&str]) -> HashMap<String, String> {
+        let mut records = HashMap::new();
+        let Some((resolver, contract_ids)) = self.get_domain_resolver_supporting(domain, TEXT_RESOLVER).await.unwrap() else {
+            return records;
+        };
+        let asset_id = self.get_domain_asset_id(domain).await;
+        for key in STANDARD_TEXT_KEYS.iter().chain(extra_keys) {
+            if let Some(value) = Self::read_text(&resolver, &contract_ids, asset_id, key).await {
+                records.insert(key.to_string(), value);
+            }
+        }
+        records
+    }
+
+    /// Validates and encodes the address of the SLIP-44 `coin_type` before setting it, `None` clears the record
+    pub async fn set_addr(&self, domain: &str, coin_type: u64, address: Option<&str>) -> Result<()> {
+        let addr = address
+            .map(|address| encode_address(coin_type, address).map(Bytes))
+            .transpose()
+            .map_err(|error| Error::Other(error.to_string()))?;
+        let (resolver, contract_ids) = self.get_domain_resolver_for_update(&self.user, domain, ADDR_RESOLVER).await?;
+        let asset_id = self.get_domain_asset_id(domain).await;
+        resolver
+            .methods()
+            .set_addr(asset_id, coin_type, addr)
+            .with_contract_ids(&contract_ids)
+            .add_custom_asset(asset_id, 1, Some(self.user.address().into()))
+            .call()
+            .await
+            .map(|response| response.value)
+    }
+
+    /// `None` as well if the domain resolver doesn't support multi-chain addresses
+    pub async fn get_addr_bytes(&self, domain: &str, coin_type: u64) -> Option<Vec<u8>> {
+        let (resolver, contract_ids) = self.get_domain_resolver_supporting(domain, ADDR_RESOLVER).await.unwrap()?;
+        let asset_id = self.get_domain_asset_id(domain).await;
+        resolver
+            .methods()
+            .addr(asset_id, coin_type)
+            .with_contract_ids(&contract_ids)
+            .simulate(Execution::StateReadOnly)
+            .await
+            .unwrap()
+            .value
+            .map(|bytes| bytes.0)
+    }
+
+    /// Fails when the stored bytes aren't a valid address of the coin type, the resolver accepts any bytes
+    pub async fn get_addr(&self, domain: &str, coin_type: u64) -> Result<Option<String>> {
+        self.get_addr_bytes(domain, coin_type)
+            .await
+            .map(|bytes| decode_address(coin_type, &bytes))
+            .transpose()
+            .map_err(|error| Error::Other(error.to_string()))
+    }
+
+    /// Encodes an `ipfs://`, `ipns://` or `ar://` uri before setting it, `None` clears the record
+    pub async fn set_contenthash(&self, domain: &str, uri: Option<&str>) -> Result<()> {
+        let hash = uri
+            .map(|uri| encode_contenthash(uri).map(Bytes))
+            .transpose()
+            .map_err(|error| Error::Other(error.to_string()))?;
+        let (resolver, contract_ids) = self.get_domain_resolver_for_update(&self.user, domain, CONTENTHASH_RESOLVER).await?;
+        let asset_id = self.get_domain_asset_id(domain).await;
+        resolver
+            .methods()
+            .set_contenthash(asset_id, hash)
+            .with_contract_ids(&contract_ids)
+            .add_custom_asset(asset_id, 1, Some(self.user.address().into()))
+            .call()
+            .await
+            .map(|response| response.value)
+    }
+
+    /// Raw stored content hash, `None` as well if the domain resolver doesn't support content hashes
+    pub async fn get_contenthash_bytes(&self, domain: &str) -> Option<Vec<u8>> {
+        let (resolver, contract_ids) = self.get_domain_resolver_supporting(domain, CONTENTHASH_RESOLVER).await.unwrap()?;
+        let asset_id = self.get_domain_asset_id(domain).await;
+        resolver
+            .methods()
+            .contenthash(asset_id)
+            .with_contract_ids(&contract_ids)
+            .simulate(Execution::StateReadOnly)
+            .await
+            .unwrap()
+            .value
+            .map(|bytes| bytes.0)
+    }
+
+    /// Fails when the stored bytes aren't a supported content hash, the resolver accepts any bytes
+    pub async fn get_contenthash(&self, domain: &str) -> Result<Option<String>> {
+        self.get_contenthash_bytes(domain)
+            .await
+            .map(|bytes| decode_contenthash(&bytes))
+            .transpose()
+            .map_err(|error| Error::Other(error.to_string()))
+    }
+
+    /// Makes all records of the domain unreadable, the user must hold the domain NFT
+    pub async fn clear_records(&self, domain: &str) -> Result<()> {
+        let asset_id = self.get_domain_asset_id(domain).await;
+        self.resolver_contract
+            .clone()
+            .with_account(self.user.clone())
+            .methods()
+            .clear_records(asset_id)
+            .with_contract_ids(&[self.resolver().target_id.into()])
+            .add_custom_asset(asset_id, 1, Some(self.user.address().into()))
+            .call()
+            .await
+            .map(|response| response.value)
+    }
+
+    pub async fn get_record_version(&self, domain: &str) -> u64 {
+        let asset_id = self.get_domain_asset_id(domain).await;
+        self.resolver_contract
+            .methods()
+            .record_version(asset_id)
+            .with_contract_ids(&[self.resolver().target_id.into()])
+            .simulate(Execution::StateReadOnly)
+            .await
+            .unwrap()
+            .value
+    }
+
+    /// Applies all updates in one transaction, see [crate::records::RecordUpdates]
+    pub async fn multicall(&self, domain: &str, updates: Vec<RecordUpdate>) -> Result<()> {
+        let (resolver, contract_ids) = self.get_domain_resolver_for_update(&self.user, domain, MULTICALL_RESOLVER).await?;
+        let asset_id = self.get_domain_asset_id(domain).await;
+        resolver
+            .methods()
+            .multicall(asset_id, updates)
+            .with_contract_ids(&contract_ids)
+            .add_custom_asset(asset_id, 1, Some(self.user.address().into()))
+            .call()
+            .await
+            .map(|response| response.value)
+    }
+
+    /// Approves or revokes an operator of a single domain, `owner` must hold the domain NFT.
+    /// Approvals of addresses outlive a plain [Fixture::transfer] until the new holder proves ownership to the
+    /// resolver (e.g. with [Fixture::clear_records]); [Fixture::transfer_domain] with `clear_records` ends them.
+    pub async fn set_operator(&self, owner: &WalletUnlocked, domain: &str, operator: Identity, approved: bool) -> Result<()> {
+        let asset_id = self.get_domain_asset_id(domain).await;
+        let mut call = self.resolver_contract
+            .clone()
+            .with_account(owner.clone())
+            .methods()
+            .set_operator(asset_id, operator, approved)
+            .with_contract_ids(&[self.resolver().target_id.into()]);
+        if self.holds_asset(owner, asset_id).await {
+            call = call.add_custom_asset(asset_id, 1, Some(owner.address().into()));
+        }
+        call.call()
+            .await
+            .map(|response| response.value)
+    }
+
+    /// Approves or revokes an operator of all domains `owner` has proven to hold in the resolver, see
+    /// [Fixture::set_operator] for transfers
+    pub async fn set_approval_for_all(&self, owner: &WalletUnlocked, operator: Identity, approved: bool) -> Result<()> {
+        self.resolver_contract
+            .clone()
+            .with_account(owner.clone())
+            .methods()
+            .set_approval_for_all(operator, approved)
+            .with_contract_ids(&[self.resolver().target_id.into()])
+            .call()
+            .await
+            .map(|response| response.value)
+    }
+
+    pub async fn is_operator(&self, domain: &str, account: Identity) -> bool {
+        let asset_id = self.get_domain_asset_id(domain).await;
+        self.resolver_contract
+            .methods()
+            .is_operator(asset_id, account)
+            .with_contract_ids(&[self.resolver().target_id.into()])
+            .simulate(Execution::StateReadOnly)
+            .await
+            .unwrap()
+            .value
+    }
+
+    pub async fn is_approved_for_all(&self, owner: Identity, operator: Identity) -> bool {
+        self.resolver_contract
+            .methods()
+            .is_approved_for_all(owner, operator)
+            .with_contract_ids(&[self.resolver().target_id.into()])
+            .simulate(Execution::StateReadOnly)
+            .await
+            .unwrap()
+            .value
+    }
+
+    pub async fn set_primary(&self, domain: &str) {
+        let asset_id: AssetId = self
+            .registry_contract
+            .methods()
+            .get_domain_asset_id(domain.to_string())
+            .with_contract_ids(&[self.registry().target_id.into()])
+            .simulate(Execution::StateReadOnly)
+            .await
+            .unwrap()
+            .value;
+        self.registry_contract
+            .clone()
+            .with_account(self.user.clone())
+            .methods()
+            .set_primary(asset_id)
+            .with_contract_ids(&[
+                self.registry().target_id.into(),
+                self.resolver().proxy_id.into(),
+                self.resolver().target_id.into(),
+            ])
+            .call()
+            .await
+            .unwrap();
+    }
+
+    pub async fn withdraw_funds(&self, asset_id: &AssetId) {
+        // one output per beneficiary or a single one for the owner
+        let outputs = self.get_beneficiaries().await.len().max(1);
+        self.registrar_contract
+            .methods()
+            .withdraw_funds(*asset_id)
+            .with_contract_ids(&[self.registrar().target_id.into()])
+            .with_variable_output_policy(VariableOutputPolicy::Exactly(outputs))
+            .call()
+            .await
+            .unwrap();
+    }
+
+    pub async fn withdraw_funds_to(&self, asset_id: &AssetId, recipient: Identity, amount: u64) {
+        self.registrar_contract
+            .methods()
+            .withdraw_funds_to(*asset_id, recipient, amount)
+            .with_contract_ids(&[self.registrar().target_id.into()])
+            .with_variable_output_policy(VariableOutputPolicy::Exactly(1))
+            .call()
+            .await
+            .unwrap();
+    }
+
+    pub async fn set_beneficiaries(&self, beneficiaries: Vec<Beneficiary>) {
+        self.registrar_contract
+            .methods()
+            .set_beneficiaries(beneficiaries)
+            .with_contract_ids(&[self.registrar().target_id.into()])
+            .call()
+            .await
+            .unwrap();
+    }
+
+    pub async fn get_beneficiaries(&self) -> Vec<Beneficiary> {
+        self.registrar_contract
+            .methods()
+            .get_beneficiaries()
+            .with_contract_ids(&[self.registrar().target_id.into()])
+            .simulate(Execution::StateReadOnly)
+            .await
+            .unwrap()
+            .value
+    }
+
+    pub async fn get_treasury_report(&self, asset_id: &AssetId) -> TreasuryReport {
+        let registrar = self.registrar_contract.methods();
+        let contract_ids: [Bech32ContractId; 1] = [self.registrar().target_id.into()];
+        let collected_revenue = registrar
+            .get_collected_revenue(*asset_id)
+            .with_contract_ids(&contract_ids)
+            .simulate(Execution::StateReadOnly)
+            .await
+            .unwrap()
+            .value;
+        let withdrawn_funds = registrar
+            .get_withdrawn_funds(*asset_id)
+            .with_contract_ids(&contract_ids)
+            .simulate(Execution::StateReadOnly)
+            .await
+            .unwrap()
+            .value;
+        let pending_funds = registrar
+            .get_pending_funds(*asset_id)
+            .with_contract_ids(&contract_ids)
+            .simulate(Execution::StateReadOnly)
+            .await
+            .unwrap()
+            .value;
+        let pending_referral_fees = registrar
+            .get_pending_referral_fees(*asset_id)
+            .with_contract_ids(&contract_ids)
+            .simulate(Execution::StateReadOnly)
+            .await
+            .unwrap()
+            .value;
+        TreasuryReport {
+            collected_revenue,
+            withdrawn_funds,
+            pending_funds,
+            pending_referral_fees,
+        }
+    }
+
+    /// Pauses the operation (or the whole contract if `None`) on the registrar or the registry.
+    pub async fn pause(&self, contract: &ContractType, operation: Option<PausableOperation>) {
+        match contract {
+            ContractType::Registrar => {
+                self.registrar_contract
+                    .clone()
+                    .with_account(self.deployer.clone())
+                    .methods()
+                    .pause(operation)
+                    .with_contract_ids(&[self.registrar().target_id.into()])
+                    .call()
+                    .await
+                    .unwrap();
+            }
+            ContractType::Registry => {
+                self.registry_contract
+                    .clone()
+                    .with_account(self.deployer.clone())
+                    .methods()
+                    .pause(operation)
+                    .with_contract_ids(&[self.registry().target_id.into()])
+                    .call()
+                    .await
+                    .unwrap();
+            }
+            ContractType::Resolver => panic!("Resolver is not pausable"),
+            ContractType::SubdomainRegistrar => panic!("Subdomain registrar is not pausable"),
+        }
+    }
+
+    pub async fn unpause(&self, contract: &ContractType, operation: Option<PausableOperation>) {
+        match contract {
+            ContractType::Registrar => {
+                self.registrar_contract
+                    .clone()
+                    .with_account(self.deployer.clone())
+                    .methods()
+                    .unpause(operation)
+                    .with_contract_ids(&[self.registrar().target_id.into()])
+                    .call()
+                    .await
+                    .unwrap();
+            }
+            ContractType::Registry => {
+                self.registry_contract
+                    .clone()
+                    .with_account(self.deployer.clone())
+                    .methods()
+                    .unpause(operation)
+                    .with_contract_ids(&[self.registry().target_id.into()])
+                    .call()
+                    .await
+                    .unwrap();
+            }
+            ContractType::Resolver => panic!("Resolver is not pausable"),
+            ContractType::SubdomainRegistrar => panic!("Subdomain registrar is not pausable"),
+        }
+    }
+
+    pub async fn paused(&self, contract: &ContractType, operation: Option<PausableOperation>) -> bool {
+        match contract {
+            ContractType::Registrar => {
+                self.registrar_contract
+                    .methods()
+                    .paused(operation)
+                    .with_contract_ids(&[self.registrar().target_id.into()])
+                    .simulate(Execution::StateReadOnly)
+                    .await
+                    .unwrap()
+                    .value
+            }
+            ContractType::Registry => {
+                self.registry_contract
+                    .methods()
+                    .paused(operation)
+                    .with_contract_ids(&[self.registry().target_id.into()])
+                    .simulate(Execution::StateReadOnly)
+                    .await
+                    .unwrap()
+                    .value
+            }
+            ContractType::Resolver | ContractType::SubdomainRegistrar => false,
+        }
+    }
+
+    pub async fn grant_role(&self, contract: &ContractType, role: Role, account: Identity) {
+        match contract {
+            ContractType::Registrar => {
+                self.registrar_contract
+                    .clone()
+                    .with_account(self.deployer.clone())
+                    .methods()
+                    .grant_role(role, account)
+                    .with_contract_ids(&[self.registrar().target_id.into()])
+                    .call()
+                    .await
+                    .unwrap();
+            }
+            ContractType::Registry => {
+                self.registry_contract
+                    .clone()
+                    .with_account(self.deployer.clone())
+                    .methods()
+                    .grant_role(role, account)
+                    .with_contract_ids(&[self.registry().target_id.into()])
+                    .call()
+                    .await
+                    .unwrap();
+            }
+            ContractType::Resolver => panic!("Resolver has no roles"),
+            ContractType::SubdomainRegistrar => panic!("Subdomain registrar has no roles"),
+        }
+    }
+
+    pub async fn revoke_role(&self, contract: &ContractType, role: Role, account: Identity) {
+        match contract {
+            ContractType::Registrar => {
+                self.registrar_contract
+                    .clone()
+                    .with_account(self.deployer.clone())
+                    .methods()
+                    .revoke_role(role, account)
+                    .with_contract_ids(&[self.registrar().target_id.into()])
+                    .call()
+                    .await
+                    .unwrap();
+            }
+            ContractType::Registry => {
+                self.registry_contract
+                    .clone()
+                    .with_account(self.deployer.clone())
+                    .methods()
+                    .revoke_role(role, account)
+                    .with_contract_ids(&[self.registry().target_id.into()])
+                    .call()
+                    .await
+                    .unwrap();
+            }
+            ContractType::Resolver => panic!("Resolver has no roles"),
+            ContractType::SubdomainRegistrar => panic!("Subdomain registrar has no roles"),
+        }
+    }
+
+    pub async fn has_role(&self, contract: &ContractType, role: Role, account: Identity) -> bool {
+        match contract {
+            ContractType::Registrar => {
+                self.registrar_contract
+                    .methods()
+                    .has_role(role, account)
+                    .with_contract_ids(&[self.registrar().target_id.into()])
+                    .simulate(Execution::StateReadOnly)
+                    .await
+                    .unwrap()
+                    .value
+            }
+            ContractType::Registry => {
+                self.registry_contract
+                    .methods()
+                    .has_role(role, account)
+                    .with_contract_ids(&[self.registry().target_id.into()])
+                    .simulate(Execution::StateReadOnly)
+                    .await
+                    .unwrap()
+                    .value
+            }
+            ContractType::Resolver | ContractType::SubdomainRegistrar => false,
+        }
+    }
+
+    /// Mints `name.parent` to `recipient` directly through the registry, `owner` presents the parent NFT
+    pub async fn register_sub_domain(
+        &self,
+        owner: &WalletUnlocked,
+        parent: &str,
+        name: &str,
+        recipient: Identity,
+        options: SubdomainOptions,
+    ) -> Result<AssetId> {
+        let parent_asset = self.get_domain_asset_id(parent).await;
+        self.registry_contract
+            .clone()
+            .with_account(owner.clone())
+            .methods()
+            .register_sub_domain_with_options(
+                recipient,
+                parent.to_string(),
+                name.to_string(),
+                None,
+                None,
+                self.resolver().proxy_id,
+                options,
+            )
+            .with_variable_output_policy(VariableOutputPolicy::Exactly(1))
+            .with_contract_ids(&[self.registry().target_id.into()])
+            .add_custom_asset(parent_asset, 1, Some(owner.address().into()))
+            .call()
+            .await
+            .map(|response| response.value)
+    }
+
+    pub async fn revoke_sub_domain(&self, owner: &WalletUnlocked, parent: &str, name: &str) -> Result<()> {
+        let parent_asset = self.get_domain_asset_id(parent).await;
+        let mut call = self.registry_contract
+            .clone()
+            .with_account(owner.clone())
+            .methods()
+            .revoke_sub_domain(name.to_string(), parent.to_string())
+            .with_contract_ids(&[self.registry().target_id.into()]);
+        if self.holds_asset(owner, parent_asset).await {
+            call = call.add_custom_asset(parent_asset, 1, Some(owner.address().into()));
+        }
+        call.call()
+            .await
+            .map(|response| response.value)
+    }
+
+    pub async fn reassign_sub_domain(
+        &self,
+        owner: &WalletUnlocked,
+        parent: &str,
+        name: &str,
+        recipient: Identity,
+    ) -> Result<AssetId> {
+        let parent_asset = self.get_domain_asset_id(parent).await;
+        let mut call = self.registry_contract
+            .clone()
+            .with_account(owner.clone())
+            .methods()
+            .reassign_sub_domain(name.to_string(), parent.to_string(), recipient)
+            .with_variable_output_policy(VariableOutputPolicy::Exactly(1))
+            .with_contract_ids(&[self.registry().target_id.into()]);
+        if self.holds_asset(owner, parent_asset).await {
+            call = call.add_custom_asset(parent_asset, 1, Some(owner.address().into()));
+        }
+        call.call()
+            .await
+            .map(|response| response.value)
+    }
+
+    pub async fn set_renewal_limit(
+        &self,
+        owner: &WalletUnlocked,
+        parent: &str,
+        name: &str,
+        max_expiration: Option<u64>,
+    ) -> Result<()> {
+        let parent_asset = self.get_domain_asset_id(parent).await;
+        let mut call = self.registry_contract
+            .clone()
+            .with_account(owner.clone())
+            .methods()
+            .set_renewal_limit(name.to_string(), parent.to_string(), max_expiration)
+            .with_contract_ids(&[self.registry().target_id.into()]);
+        if self.holds_asset(owner, parent_asset).await {
+            call = call.add_custom_asset(parent_asset, 1, Some(owner.address().into()));
+        }
+        call.call()
+            .await
+            .map(|response| response.value)
+    }
+
+    pub async fn get_renewal_limit(&self, domain: &str) -> Option<u64> {
+        self.registry_contract
+            .methods()
+            .get_renewal_limit(domain.to_string())
+            .with_contract_ids(&[self.registry().target_id.into()])
+            .simulate(Execution::StateReadOnly)
+            .await
+            .unwrap()
+            .value
+    }
+
+    /// Renews `domain` as its holder within the limit approved by the parent
+    pub async fn self_renew_sub_domain(&self, holder: &WalletUnlocked, domain: &str, expiration: u64) -> Result<()> {
+        let asset_id = self.get_domain_asset_id(domain).await;
+        let mut call = self.registry_contract
+            .clone()
+            .with_account(holder.clone())
+            .methods()
+            .self_renew_sub_domain(domain.to_string(), expiration)
+            .with_contract_ids(&[self.registry().target_id.into()]);
+        if self.holds_asset(holder, asset_id).await {
+            call = call.add_custom_asset(asset_id, 1, Some(holder.address().into()));
+        }
+        call.call()
+            .await
+            .map(|response| response.value)
+    }
+
+    pub async fn get_subdomains(&self, parent: &str, offset: u64, limit: u64) -> Vec<AssetId> {
+        self.registry_contract
+            .methods()
+            .get_subdomains(parent.to_string(), offset, limit)
+            .with_contract_ids(&[self.registry().target_id.into()])
+            .simulate(Execution::StateReadOnly)
+            .await
+            .unwrap()
+            .value
+    }
+
+    pub async fn get_subdomain_count(&self, parent: &str) -> u64 {
+        self.registry_contract
+            .methods()
+            .get_subdomain_count(parent.to_string())
+            .with_contract_ids(&[self.registry().target_id.into()])
+            .simulate(Execution::StateReadOnly)
+            .await
+            .unwrap()
+            .value
+    }
+
+    /// Names of all active subdomains of `parent`, fetched page by page. Pages larger than
+    /// [MAX_SUBDOMAINS_PAGE_SIZE] are capped by the registry, so `page_size` is clamped to it.
+    pub async fn list_subdomains(&self, parent: &str, page_size: u64) -> Result<Vec<String>> {
+        if page_size == 0 {
+            return Err(Error::Other("page size must be positive".to_string()));
+        }
+        let page_size = page_size.min(MAX_SUBDOMAINS_PAGE_SIZE);
+        let count = self.get_subdomain_count(parent).await;
+        let mut names = vec![];
+        let mut offset = 0;
+        while offset < count {
+            for asset in self.get_subdomains(parent, offset, page_size).await {
+                names.push(self.get_domain_name(asset).await);
+            }
+            offset += page_size;
+        }
+        Ok(names)
+    }
+
+    pub async fn get_subdomain_options(&self, asset_id: AssetId) -> Option<SubdomainOptions> {
+        self.registry_contract
+            .methods()
+            .get_subdomain_options(asset_id)
+            .with_contract_ids(&[self.registry().target_id.into()])
+            .simulate(Execution::StateReadOnly)
+            .await
+            .unwrap()
+            .value
+    }
+
+    /// Hands the parent NFT over to the subdomain registrar
+    pub async fn deposit_domain(&self, owner: &WalletUnlocked, domain: &str) -> Result<()> {
+        let asset_id = self.get_domain_asset_id(domain).await;
+        self.subdomain_registrar_contract(owner)
+            .methods()
+            .deposit_domain(domain.to_string())
+            .call_params(
+                CallParameters::default()
+                    .with_amount(1)
+                    .with_asset_id(asset_id),
+            )
+            .unwrap()
+            .with_contract_ids(&[
+                self.subdomain_registrar().target_id.into(),
+                self.registry().proxy_id.into(),
+                self.registry().target_id.into()
+            ])
+            .call()
+            .await
+            .map(|response| response.value)
+    }
+
+    pub async fn withdraw_domain(&self, owner: &WalletUnlocked, domain: &str) -> Result<()> {
+        let asset_id = self.get_domain_asset_id(domain).await;
+        self.subdomain_registrar_contract(owner)
+            .methods()
+            .withdraw_domain(asset_id)
+            .with_variable_output_policy(VariableOutputPolicy::Exactly(1))
+            .with_contract_ids(&[self.subdomain_registrar().target_id.into()])
+            .call()
+            .await
+            .map(|response| response.value)
+    }
+
+    pub async fn set_subdomain_terms(&self, owner: &WalletUnlocked, domain: &str, terms: Option<SubdomainTerms>) -> Result<()> {
+        let asset_id = self.get_domain_asset_id(domain).await;
+        self.subdomain_registrar_contract(owner)
+            .methods()
+            .set_subdomain_terms(asset_id, terms)
+            .with_contract_ids(&[self.subdomain_registrar().target_id.into()])
+            .call()
+            .await
+            .map(|response| response.value)
+    }
+
+    pub async fn get_subdomain_terms(&self, domain: &str) -> Option<SubdomainTerms> {
+        let asset_id = self.get_domain_asset_id(domain).await;
+        self.subdomain_registrar_contract(&self.user)
+            .methods()
+            .get_subdomain_terms(asset_id)
+            .with_contract_ids(&[self.subdomain_registrar().target_id.into()])
+            .simulate(Execution::StateReadOnly)
+            .await
+            .unwrap()
+            .value
+    }
+
+    pub async fn get_parent_owner(&self, domain: &str) -> Option<Identity> {
+        let asset_id = self.get_domain_asset_id(domain).await;
+        self.subdomain_registrar_contract(&self.user)
+            .methods()
+            .get_parent_owner(asset_id)
+            .with_contract_ids(&[self.subdomain_registrar().target_id.into()])
+            .simulate(Execution::StateReadOnly)
+            .await
+            .unwrap()
+            .value
+    }
+
+    pub async fn set_allowlisted(&self, owner: &WalletUnlocked, domain: &str, account: Identity, allowed: bool) -> Result<()> {
+        let asset_id = self.get_domain_asset_id(domain).await;
+        self.subdomain_registrar_contract(owner)
+            .methods()
+            .set_allowlisted(asset_id, account, allowed)
+            .with_contract_ids(&[self.subdomain_registrar().target_id.into()])
+            .call()
+            .await
+            .map(|response| response.value)
+    }
+
+    /// Revokes a controlled subdomain of a parent deposited in the subdomain registrar
+    pub async fn revoke_claimed_subdomain(&self, owner: &WalletUnlocked, parent_domain: &str, name: &str) -> Result<()> {
+        self.subdomain_registrar_contract(owner)
+            .methods()
+            .revoke_subdomain(parent_domain.to_string(), name.to_string())
+            .with_contract_ids(&[
+                self.subdomain_registrar().target_id.into(),
+                self.registry().proxy_id.into(),
+                self.registry().target_id.into()
+            ])
+            .call()
+            .await
+            .map(|response| response.value)
+    }
+
+    /// Mints `name.parent_domain` to `claimer` through the subdomain registrar
+    pub async fn claim_subdomain(
+        &self,
+        claimer: &WalletUnlocked,
+        parent_domain: &str,
+        name: &str,
+        fee_to_transfer: u64,
+        asset: Option<AssetId>,
+    ) -> Result<AssetId> {
+        let tx_policies = TxPolicies::default()
+            .with_script_gas_limit(1_000_000);
+        self.subdomain_registrar_contract(claimer)
+            .methods()
+            .claim_subdomain(parent_domain.to_string(), name.to_string())
+            // minted NFT, fee forwarded to the parent owner and a possible overpayment refund
+            .with_variable_output_policy(VariableOutputPolicy::Exactly(3))
+            .with_tx_policies(tx_policies)
+            .call_params(
+                CallParameters::default()
+                    .with_amount(fee_to_transfer)
+                    .with_asset_id(asset.unwrap_or(AssetId::BASE)),
+            )
+            .unwrap()
+            .with_contract_ids(&[
+                self.subdomain_registrar().target_id.into(),
+                self.registry().proxy_id.into(),
+                self.registry().target_id.into()
+            ])
+            .call()
+            .await
+            .map(|response| response.value)
+    }
+
+    pub async fn get_total_assets(&self) -> u64 {
+        self.registry_contract
+            .methods()
+            .total_assets()
+            .with_contract_ids(&[self.registry().target_id.into()])
+            .simulate(Execution::StateReadOnly)
+            .await
+            .unwrap()
+            .value
+    }
+
+    /// Sends the NFT directly. The resolver doesn't see it, so operators of `owner` keep editing the records until
+    /// the recipient proves ownership to the resolver
+    pub async fn transfer(&self, owner: &WalletUnlocked, domain: &str, to: &Bech32Address) {
+        let asset_id: AssetId = self.get_domain_asset_id(domain).await;
+        owner
+            .transfer(to, 1, asset_id, TxPolicies::default())
+            .await
+            .unwrap();
+    }
+
+    /// Sends the NFT through the registry, which wipes the records of `owner` first if `clear_records` is set
+    pub async fn transfer_domain(&self, owner: &WalletUnlocked, domain: &str, to: &Bech32Address, clear_records: bool) -> Result<()> {
+        let asset_id = self.get_domain_asset_id(domain).await;
+        self.registry_contract
+            .clone()
+            .with_account(owner.clone())
+            .methods()
+            .transfer_domain(Identity::Address(to.into()), clear_records)
+            .call_params(
+                CallParameters::default()
+                    .with_amount(1)
+                    .with_asset_id(asset_id),
+            )
+            .unwrap()
+            .with_variable_output_policy(VariableOutputPolicy::Exactly(1))
+            .with_contract_ids(&[
+                self.registry().target_id.into(),
+                self.resolver().proxy_id.into(),
+                self.resolver().target_id.into(),
+            ])
+            .call()
+            .await
+            .map(|response| response.value)
+    }
+
+    pub async fn set_fees(
+        &self,
+        asset: &AssetId,
+        three_letter_fee: u64,
+        four_letter_fee: u64,
+        long_domain_fee: u64,
+    ) {
+        self.registrar_contract
+            .methods()
+            .set_fees(*asset, three_letter_fee, four_letter_fee, long_domain_fee)
+            .with_contract_ids(&[self.registrar().target_id.into()])
+            .call()
+            .await
+            .unwrap();
+    }
+
+    pub async fn set_grace_period(&self, duration: u64) {
         self.registrar_contract
             .methods()
             .set_grace_period(duration)
@@ -372,6 +1734,57 @@ impl Fixture {
             .value
     }
 
+    pub async fn set_root_domain(&self, root_domain: &str) {
+        self.registrar_contract
+            .methods()
+            .set_root_domain(root_domain.to_string())
+            .with_contract_ids(&[
+                self.registrar().target_id.into(),
+                self.registry().proxy_id.into(),
+                self.registry().target_id.into(),
+            ])
+            .call()
+            .await
+            .unwrap();
+    }
+
+    /// `domain` under the root domain of the registrar
+    pub async fn full_domain_name(&self, domain: &str) -> String {
+        format!("{}.{}", domain, self.get_root_domain().await)
+    }
+
+    pub async fn get_root_domain(&self) -> String {
+        self.registrar_contract
+            .methods()
+            .get_root_domain()
+            .with_contract_ids(&[self.registrar().target_id.into()])
+            .simulate(Execution::StateReadOnly)
+            .await
+            .unwrap()
+            .value
+    }
+
+    pub async fn set_period_limits(&self, min_periods: u64, max_periods: u64, period_duration: u64) {
+        self.registrar_contract
+            .methods()
+            .set_period_limits(min_periods, max_periods, period_duration)
+            .with_contract_ids(&[self.registrar().target_id.into()])
+            .call()
+            .await
+            .unwrap();
+    }
+
+    pub async fn get_period_limits(&self) -> PeriodLimits {
+        self.registrar_contract
+            .methods()
+            .get_period_limits()
+            .with_contract_ids(&[self.registrar().target_id.into()])
+            .simulate(Execution::StateReadOnly)
+            .await
+            .unwrap()
+            .value
+    }
+
     pub async fn renew_domain(
         &self,
         domain: &str,
@@ -383,6 +1796,8 @@ impl Fixture {
             .with_account(self.user.clone())
             .methods()
             .renew_domain(domain.to_string(), years)
+            // possible overpayment refund
+            .with_variable_output_policy(VariableOutputPolicy::Exactly(1))
             .with_contract_ids(&[
                 self.registrar().target_id.into(),
                 self.registry().proxy_id.into(),
@@ -432,6 +1847,7 @@ impl Fixture {
         let registry = contracts.get(&ContractType::Registry).unwrap();
         let resolver = contracts.get(&ContractType::Resolver).unwrap();
         let registrar = contracts.get(&ContractType::Registrar).unwrap();
+        // contracts may lack the subdomain registrar, its methods panic in that case
         Fixture {
             deployer: deployer.clone(),
             user: user.clone(),
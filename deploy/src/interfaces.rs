@@ -0,0 +1,19 @@
+use fuels::crypto::Hasher;
+use fuels::types::Bits256;
+
+/// ABI names of the resolver interfaces, see `supports_interface` in `shared`
+pub const INTERFACE_SUPPORT: &str = "InterfaceSupport";
+pub const BASE_RESOLVER: &str = "BaseDomainResolver";
+pub const SIMPLE_RESOLVER: &str = "SimpleDomainResolver";
+pub const CHECKED_RESOLVER: &str = "CheckedDomainResolver";
+pub const TEXT_RESOLVER: &str = "TextResolver";
+pub const ADDR_RESOLVER: &str = "AddrResolver";
+pub const CONTENTHASH_RESOLVER: &str = "ContenthashResolver";
+pub const VERSIONED_RESOLVER: &str = "VersionedResolver";
+pub const OPERATOR_RESOLVER: &str = "OperatorResolver";
+pub const MULTICALL_RESOLVER: &str = "MulticallResolver";
+
+/// The interface id is the `sha256` of the ABI name
+pub fn interface_id(name: &str) -> Bits256 {
+    Bits256(*Hasher::hash(name.as_bytes()))
+}
@@ -1,3 +1,9 @@
 pub mod fixture;
 pub mod deployer;
 pub mod shared;
+pub mod premium;
+pub mod voucher;
+pub mod address_codec;
+pub mod contenthash;
+pub mod records;
+pub mod interfaces;
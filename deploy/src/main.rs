@@ -2,6 +2,11 @@ use crate::deployer::DeployTarget;
 mod deployer;
 mod fixture;
 mod shared;
+mod premium;
+mod voucher;
+mod address_codec;
+mod contenthash;
+mod interfaces;
 
 #[tokio::main]
 async fn main() {
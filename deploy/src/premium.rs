@@ -0,0 +1,36 @@
+/// Premium configuration of the registrar for a single fee asset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PremiumConfig {
+    pub start_premium: u64,
+    pub decay_period: u64,
+}
+
+/// Premium charged at `at` for a domain released (expiration + grace period) at `released_at`.
+/// Mirrors the linear decay implemented by the registrar, both timestamps must use the same clock
+/// (the registry stores TAI64 timestamps).
+pub fn premium_at(config: PremiumConfig, released_at: u64, at: u64) -> u64 {
+    if config.start_premium == 0 || config.decay_period == 0 {
+        return 0;
+    }
+    if at < released_at || at - released_at >= config.decay_period {
+        return 0;
+    }
+    let remaining = config.decay_period - (at - released_at);
+    (config.start_premium as u128 * remaining as u128 / config.decay_period as u128) as u64
+}
+
+/// Premium values from the release moment until the premium reaches zero, sampled every `step` seconds.
+pub fn premium_schedule(config: PremiumConfig, released_at: u64, step: u64) -> Vec<(u64, u64)> {
+    assert!(step > 0, "step must be positive");
+    let mut schedule = vec![];
+    let mut at = released_at;
+    loop {
+        let premium = premium_at(config, released_at, at);
+        schedule.push((at, premium));
+        if premium == 0 {
+            break;
+        }
+        at += step;
+    }
+    schedule
+}
@@ -0,0 +1,51 @@
+use fuels::types::{Bytes, Identity};
+
+use crate::address_codec::{encode_address, AddressCodecError};
+use crate::contenthash::{encode_contenthash, ContenthashError};
+use crate::deployer::RecordUpdate;
+
+/// Collects record changes for a single resolver `multicall`, addresses and content hashes are validated and encoded
+/// while they are added.
+///
+/// ```ignore
+/// let updates = RecordUpdates::new()
+///     .text("url", Some("https://fuelname.com"))
+///     .addr(COIN_TYPE_ETH, Some("0x314159265dd8dbb310642f98f50c066173c1259b"))?
+///     .build();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct RecordUpdates {
+    updates: Vec<RecordUpdate>,
+}
+
+impl RecordUpdates {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn address(mut self, identity: Option<Identity>) -> Self {
+        self.updates.push(RecordUpdate::Address(identity));
+        self
+    }
+
+    pub fn text(mut self, key: &str, value: Option<&str>) -> Self {
+        self.updates.push(RecordUpdate::Text((key.to_string(), value.map(|value| value.to_string()))));
+        self
+    }
+
+    pub fn addr(mut self, coin_type: u64, address: Option<&str>) -> Result<Self, AddressCodecError> {
+        let addr = address.map(|address| encode_address(coin_type, address).map(Bytes)).transpose()?;
+        self.updates.push(RecordUpdate::Addr((coin_type, addr)));
+        Ok(self)
+    }
+
+    pub fn contenthash(mut self, uri: Option<&str>) -> Result<Self, ContenthashError> {
+        let hash = uri.map(|uri| encode_contenthash(uri).map(Bytes)).transpose()?;
+        self.updates.push(RecordUpdate::Contenthash(hash));
+        Ok(self)
+    }
+
+    pub fn build(self) -> Vec<RecordUpdate> {
+        self.updates
+    }
+}
@@ -2,7 +2,8 @@ use std::str::FromStr;
 use dotenvy::dotenv;
 use fuels::crypto::SecretKey;
 use fuels::prelude::{Provider, WalletUnlocked};
-use crate::deployer::{DeployParams, ProxiesInfo};
+use fuels::types::{Address, Identity};
+use crate::deployer::{DeployParams, Fees, ProxiesInfo, Role, RoleAssignment, TldRegistrarParams};
 
 #[derive(Clone)]
 pub struct Config {
@@ -10,6 +11,10 @@ pub struct Config {
     pub deployer_pk: String,
     pub user_pk: String,
     pub deploy_params: DeployParams,
+    pub initial_roles: Vec<RoleAssignment>,
+    pub extra_tld: Option<TldRegistrarParams>,
+    /// grant all roles to the deployer after an upgrade
+    pub grant_owner_roles: bool,
 }
 
 pub fn config() -> Config {
@@ -22,6 +27,7 @@ pub fn config() -> Config {
                 registrar: std::env::var("REGISTRAR_PROXY").expect("missing env var REGISTRAR_PROXY").parse().unwrap(),
                 registry: std::env::var("REGISTRY_PROXY").expect("missing env var REGISTRY_PROXY").parse().unwrap(),
                 resolver: std::env::var("RESOLVER_PROXY").expect("missing env var RESOLVER_PROXY").parse().unwrap(),
+                subdomain_registrar: std::env::var("SUBDOMAIN_REGISTRAR_PROXY").ok().filter(|id| !id.is_empty()).map(|id| id.parse().unwrap()),
             }
         ),
         _ => {
@@ -33,9 +39,58 @@ pub fn config() -> Config {
         deployer_pk: std::env::var("DEPLOYER_PK").expect("missing env var DEPLOYER_PK"),
         user_pk: std::env::var("USER_PK").expect("missing env var USER_PK"),
         deploy_params,
+        initial_roles: initial_roles(),
+        extra_tld: extra_tld(),
+        grant_owner_roles: std::env::var("GRANT_OWNER_ROLES").map(|value| value == "true").unwrap_or(false),
     }
 }
 
+
+/// Reads optional EXTRA_TLD with its base asset fees in EXTRA_TLD_FEES (three letter, four letter, long domain)
+/// and the proxy of its registrar in EXTRA_TLD_REGISTRAR_PROXY once deployed
+fn extra_tld() -> Option<TldRegistrarParams> {
+    let root_domain = std::env::var("EXTRA_TLD").unwrap_or_default();
+    if root_domain.is_empty() {
+        return None;
+    }
+    let fees: Vec<u64> = std::env::var("EXTRA_TLD_FEES")
+        .expect("missing env var EXTRA_TLD_FEES")
+        .split(',')
+        .map(|fee| fee.trim().parse().unwrap())
+        .collect();
+    assert_eq!(fees.len(), 3, "EXTRA_TLD_FEES must contain three comma separated fees");
+    Some(TldRegistrarParams {
+        root_domain,
+        fees: Fees {
+            three_letter_annual_fee: fees[0],
+            four_letter_annual_fee: fees[1],
+            long_domain_annual_fee: fees[2],
+        },
+        proxy: std::env::var("EXTRA_TLD_REGISTRAR_PROXY").ok().filter(|id| !id.is_empty()).map(|id| id.parse().unwrap()),
+    })
+}
+
+/// Reads optional comma separated lists of addresses for every role
+fn initial_roles() -> Vec<RoleAssignment> {
+    let role_vars = [
+        ("PRICING_ADMIN", Role::PricingAdmin),
+        ("TREASURER", Role::Treasurer),
+        ("TLD_ADMIN", Role::TldAdmin),
+        ("PAUSER", Role::Pauser),
+    ];
+    let mut roles = vec![];
+    for (var, role) in role_vars {
+        let addresses = std::env::var(var).unwrap_or_default();
+        for address in addresses.split(',').map(|a| a.trim()).filter(|a| !a.is_empty()) {
+            roles.push(RoleAssignment {
+                role: role.clone(),
+                account: Identity::Address(Address::from_str(address).unwrap()),
+            });
+        }
+    }
+    roles
+}
+
 pub async fn get_wallets(
     config: &Config
 ) -> (WalletUnlocked, WalletUnlocked) {
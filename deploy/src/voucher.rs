@@ -0,0 +1,66 @@
+use fuels::crypto::{Hasher, Message, SecretKey, Signature};
+use fuels::types::{Bits256, ContractId, Identity, B512};
+
+use crate::deployer::DiscountVoucher;
+
+/// Offset between UNIX and TAI64 timestamps as used by `timestamp()` on Fuel.
+const TAI64_OFFSET: u64 = (1 << 62) + 10;
+
+pub fn unix_to_tai64(unix_timestamp: u64) -> u64 {
+    unix_timestamp + TAI64_OFFSET
+}
+
+/// Hash of a voucher as computed by the registrar:
+/// `sha256(((registrar, id, discount_bps, expires_at), buyer, domain))`.
+pub fn voucher_hash(
+    registrar: ContractId,
+    id: [u8; 32],
+    discount_bps: u64,
+    expires_at: u64,
+    buyer: &Identity,
+    domain: &str,
+) -> [u8; 32] {
+    let mut hasher = Hasher::default();
+    hasher.input(*registrar);
+    hasher.input(id);
+    hasher.input(discount_bps.to_be_bytes());
+    hasher.input(expires_at.to_be_bytes());
+    // `Identity` is hashed as its variant index followed by the inner value
+    match buyer {
+        Identity::Address(address) => {
+            hasher.input([0u8]);
+            hasher.input(**address);
+        }
+        Identity::ContractId(contract_id) => {
+            hasher.input([1u8]);
+            hasher.input(**contract_id);
+        }
+    }
+    hasher.input(domain.as_bytes());
+    *hasher.finalize()
+}
+
+/// Signs a single-use discount voucher for the given registrar (proxy) contract, usable only by `buyer`
+/// to mint `domain` (without the TLD). `expires_at` is a TAI64 timestamp, see [unix_to_tai64].
+pub fn sign_voucher(
+    signer: &SecretKey,
+    registrar: ContractId,
+    buyer: &Identity,
+    domain: &str,
+    id: [u8; 32],
+    discount_bps: u64,
+    expires_at: u64,
+) -> DiscountVoucher {
+    let message = Message::from_bytes(voucher_hash(registrar, id, discount_bps, expires_at, buyer, domain));
+    let signature: [u8; 64] = *Signature::sign(signer, &message);
+    let mut first = [0u8; 32];
+    let mut second = [0u8; 32];
+    first.copy_from_slice(&signature[..32]);
+    second.copy_from_slice(&signature[32..]);
+    DiscountVoucher {
+        id: Bits256(id),
+        discount_bps,
+        expires_at,
+        signature: B512::from((Bits256(first), Bits256(second))),
+    }
+}
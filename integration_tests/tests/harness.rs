@@ -1,9 +1,16 @@
 use std::str::FromStr;
 use fuels::prelude::*;
-use fuels::types::Identity;
+use fuels::types::{Bytes, Identity};
+use deploy::address_codec::{decode_address, encode_address, AddressCodecError, COIN_TYPE_BTC, COIN_TYPE_ETH, COIN_TYPE_SOL};
+use deploy::contenthash::{decode_contenthash, encode_contenthash, ContenthashError};
 use deploy::deployer;
-use deploy::deployer::{DeployParams, DeployTarget, LocalDeployParams};
+use deploy::deployer::{Beneficiary, ContractType, Resolver, DeployParams, DeployTarget, Fees, LocalDeployParams, PausableOperation, RecordUpdate, Role, RoleAssignment, SubdomainMode, SubdomainOptions, SubdomainTerms, TldRegistrarParams};
 use deploy::fixture::Fixture;
+use deploy::records::RecordUpdates;
+use deploy::interfaces::{interface_id, ADDR_RESOLVER, BASE_RESOLVER, CONTENTHASH_RESOLVER, INTERFACE_SUPPORT, MULTICALL_RESOLVER, TEXT_RESOLVER};
+use deploy::premium::{premium_at, premium_schedule, PremiumConfig};
+use deploy::voucher::unix_to_tai64;
+use fuels::crypto::SecretKey;
 
 const THREE_LETTER_ANNUAL_DEFAULT_FEE: u64 = 50000000;
 const FOUR_LETTER_ANNUAL_DEFAULT_FEE: u64 = 10000000;
@@ -16,6 +23,7 @@ const SUB_DOMAIN_PART_1: &str = "fuelname";
 const SUB_DOMAIN_PART_2: &str = "domain";
 const SUB_DOMAIN_1: &str = "fuelname.fuel";
 const SUB_DOMAIN_2: &str = "domain.fuel";
+const EXTRA_HIGH_LEVEL_DOMAIN: &str = "name";
 const COMMON_DEFAULT_FEE: u64 = COMMON_ANNUAL_DEFAULT_FEE;
 const THREE_LETTER_DEFAULT_FEE: u64 = THREE_LETTER_ANNUAL_DEFAULT_FEE;
 const FOUR_LETTER_DEFAULT_FEE: u64 = FOUR_LETTER_ANNUAL_DEFAULT_FEE;
@@ -24,6 +32,10 @@ pub fn usdc_asset_id() -> AssetId {
     AssetId::from_str("0x286c479da40dc953bddc3bb4c453b608bba2e0ac483b077bd475174115395e6b").unwrap()
 }
 
+fn hex_to_bytes(hex: &str) -> Vec<u8> {
+    (0..hex.len()).step_by(2).map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap()).collect()
+}
+
 async fn get_custom_wallets() -> (WalletUnlocked, WalletUnlocked) {
     let wallets = launch_custom_provider_and_get_wallets(
         WalletsConfig::new_multiple_assets(
@@ -49,12 +61,46 @@ async fn get_custom_wallets() -> (WalletUnlocked, WalletUnlocked) {
     (wallets[0].clone(), wallets[1].clone())
 }
 
+fn voucher_signer() -> (SecretKey, Address) {
+    let secret = SecretKey::random(&mut rand::thread_rng());
+    let address = WalletUnlocked::new_from_private_key(secret, None).address().into();
+    (secret, address)
+}
+
 async fn setup() -> Fixture {
+    setup_with_roles(|_| vec![]).await
+}
+
+async fn setup_extra_tld(fixture: &Fixture) -> Fixture {
+    setup_extra_tld_with_proxy(fixture, None).await
+}
+
+async fn setup_extra_tld_with_proxy(fixture: &Fixture, proxy: Option<ContractId>) -> Fixture {
+    deployer::deploy_tld_registrar(fixture, &TldRegistrarParams {
+        root_domain: EXTRA_HIGH_LEVEL_DOMAIN.to_string(),
+        fees: Fees {
+            three_letter_annual_fee: 300,
+            four_letter_annual_fee: 200,
+            long_domain_annual_fee: 100,
+        },
+        proxy,
+    }).await
+}
+
+fn subdomain_options(mode: SubdomainMode, inherit_expiration: bool) -> SubdomainOptions {
+    SubdomainOptions { mode, inherit_expiration }
+}
+
+async fn setup_with_roles<F>(initial_roles: F) -> Fixture where
+    F: Fn(&WalletUnlocked) -> Vec<RoleAssignment>,
+{
     let (deployer, user) = get_custom_wallets().await;
+    let initial_roles = initial_roles(&user);
     let params: LocalDeployParams = LocalDeployParams {
         deployer_wallet: deployer,
         user_wallet: user,
         deploy_params: DeployParams::InitialDeploy,
+        initial_roles,
     };
     deployer::deploy(DeployTarget::Local(params)).await
 }
@@ -270,6 +316,9 @@ mod tests {
 
         let default_resolver = fixture.get_domain_resolver(&domain_name).await.unwrap();
         assert_eq!(default_resolver, original_resolver);
+        // random ids can't pass the interface probe
+        fixture.set_trusted_resolver(new_resolver, true).await.unwrap();
+        fixture.set_trusted_resolver(additional_resolver, true).await.unwrap();
 
         fixture.set_domain_resolver(&domain_name, new_resolver).await;
         assert_eq!(fixture.get_domain_resolver(&domain_name).await.unwrap(), new_resolver);
@@ -533,7 +582,60 @@ mod tests {
         fixture.set_fees(&usdc_asset_id(), 1000, 100, 10).await;
         let price = fixture.get_domain_price(SUB_DOMAIN_PART_1, 1, &usdc_asset_id()).await;
         assert_eq!(price, 10);
-        fixture._mint_domain(SUB_DOMAIN_PART_1, 1, 11, Some(usdc_asset_id())).await.unwrap();
+        fixture._mint_domain(SUB_DOMAIN_PART_1, 1, 9, Some(usdc_asset_id())).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_exact_fee_amount() {
+        let fixture = setup().await;
+        fixture.set_fees(&usdc_asset_id(), 1000, 100, 10).await;
+        let balance = fixture.user.get_asset_balance(&usdc_asset_id()).await.unwrap();
+        fixture._mint_domain(SUB_DOMAIN_PART_1, 1, 10, Some(usdc_asset_id())).await.unwrap();
+        let updated_balance = fixture.user.get_asset_balance(&usdc_asset_id()).await.unwrap();
+        assert_eq!(updated_balance, balance - 10);
+    }
+
+    #[tokio::test]
+    async fn test_overpayment_is_refunded() {
+        let fixture = setup().await;
+        fixture.set_fees(&usdc_asset_id(), 1000, 100, 10).await;
+        let balance = fixture.user.get_asset_balance(&usdc_asset_id()).await.unwrap();
+        let asset = fixture._mint_domain(SUB_DOMAIN_PART_1, 1, 25, Some(usdc_asset_id())).await.unwrap();
+        let updated_balance = fixture.user.get_asset_balance(&usdc_asset_id()).await.unwrap();
+        assert_eq!(updated_balance, balance - 10);
+        assert_eq!(fixture.user.get_asset_balance(&asset).await.unwrap(), 1);
+
+        let registrar_balance = fixture.deployer.try_provider().unwrap()
+            .get_contract_asset_balance(fixture.registrar_contract.contract_id(), usdc_asset_id())
+            .await
+            .unwrap();
+        assert_eq!(registrar_balance, 10);
+    }
+
+    #[tokio::test]
+    async fn test_renewal_overpayment_is_refunded() {
+        let fixture = setup().await;
+        fixture.mint_domain(SUB_DOMAIN_PART_1, 1, COMMON_DEFAULT_FEE).await.unwrap();
+        let expiration_before = fixture.get_domain_expiration(SUB_DOMAIN_1).await.unwrap();
+        let registrar_balance_before = fixture.deployer.try_provider().unwrap()
+            .get_contract_asset_balance(fixture.registrar_contract.contract_id(), BASE_ASSET_ID)
+            .await
+            .unwrap();
+        fixture.renew_domain(SUB_DOMAIN_PART_1, 1, COMMON_DEFAULT_FEE * 3).await;
+        let registrar_balance_after = fixture.deployer.try_provider().unwrap()
+            .get_contract_asset_balance(fixture.registrar_contract.contract_id(), BASE_ASSET_ID)
+            .await
+            .unwrap();
+        assert_eq!(registrar_balance_after - registrar_balance_before, COMMON_DEFAULT_FEE);
+        assert_eq!(fixture.get_domain_expiration(SUB_DOMAIN_1).await.unwrap(), expiration_before + ONE_YEAR_SECONDS);
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "WrongFeeAmount")]
+    async fn test_renewal_underpayment() {
+        let fixture = setup().await;
+        fixture.mint_domain(SUB_DOMAIN_PART_1, 1, COMMON_DEFAULT_FEE).await.unwrap();
+        fixture.renew_domain(SUB_DOMAIN_PART_1, 1, COMMON_DEFAULT_FEE - 1).await;
     }
 
     #[tokio::test]
@@ -544,4 +646,1517 @@ mod tests {
         assert!(fixture.mint_domain(SUB_DOMAIN_PART_2, 1, COMMON_DEFAULT_FEE).await.is_err());
         fixture.get_domain_price(SUB_DOMAIN_PART_2, 1, &BASE_ASSET_ID).await;
     }
+
+    #[tokio::test]
+    async fn test_no_premium_for_unregistered_domain() {
+        let fixture = setup().await;
+        fixture.set_expired_premium(&BASE_ASSET_ID, COMMON_DEFAULT_FEE * 100).await;
+        assert_eq!(fixture.get_expired_domain_premium(SUB_DOMAIN_PART_1, &BASE_ASSET_ID).await, 0);
+        assert_eq!(fixture.get_domain_price(SUB_DOMAIN_PART_1, 1, &BASE_ASSET_ID).await, COMMON_DEFAULT_FEE);
+    }
+
+    #[tokio::test]
+    async fn test_expired_premium_decays() {
+        let fixture = setup().await;
+        let start_premium = COMMON_DEFAULT_FEE * 100;
+        fixture.set_expired_premium(&BASE_ASSET_ID, start_premium).await;
+        fixture.set_premium_decay_period(28 * 24 * 3600).await;
+        fixture.mint_domain(SUB_DOMAIN_PART_1, 1, COMMON_DEFAULT_FEE).await.unwrap();
+        fixture.skip_n_days(400, true).await; // 4 days after exp + grace
+
+        let premium = fixture.get_expired_domain_premium(SUB_DOMAIN_PART_1, &BASE_ASSET_ID).await;
+        assert!(premium > 0 && premium < start_premium);
+        assert_eq!(fixture.get_domain_price(SUB_DOMAIN_PART_1, 1, &BASE_ASSET_ID).await, COMMON_DEFAULT_FEE + premium);
+
+        fixture.skip_n_days(14, true).await;
+        let decayed_premium = fixture.get_expired_domain_premium(SUB_DOMAIN_PART_1, &BASE_ASSET_ID).await;
+        assert!(decayed_premium > 0 && decayed_premium < premium);
+
+        assert_eq!(fixture.get_expired_premium(&BASE_ASSET_ID).await, start_premium);
+        let schedule = fixture.preview_expired_premium(SUB_DOMAIN_PART_1, &BASE_ASSET_ID, 24 * 3600).await;
+        assert_eq!(schedule.first().unwrap().1, start_premium);
+        assert_eq!(schedule.last().unwrap().1, 0);
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "WrongFeeAmount")]
+    async fn test_mint_expired_without_premium() {
+        let fixture = setup().await;
+        fixture.set_expired_premium(&BASE_ASSET_ID, COMMON_DEFAULT_FEE * 100).await;
+        fixture.mint_domain(SUB_DOMAIN_PART_1, 1, COMMON_DEFAULT_FEE).await.unwrap();
+        fixture.skip_n_days(400, true).await;
+        fixture.mint_domain(SUB_DOMAIN_PART_1, 1, COMMON_DEFAULT_FEE).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_mint_expired_with_premium() {
+        let fixture = setup().await;
+        let start_premium = COMMON_DEFAULT_FEE * 100;
+        fixture.set_expired_premium(&BASE_ASSET_ID, start_premium).await;
+        let initial_asset = fixture.mint_domain(SUB_DOMAIN_PART_1, 1, COMMON_DEFAULT_FEE).await.unwrap();
+        fixture.skip_n_days(400, true).await;
+        let new_asset = fixture.mint_domain(SUB_DOMAIN_PART_1, 1, COMMON_DEFAULT_FEE + start_premium).await.unwrap();
+        assert_ne!(initial_asset, new_asset);
+    }
+
+    #[tokio::test]
+    async fn test_mint_expired_after_premium_decay() {
+        let fixture = setup().await;
+        fixture.set_expired_premium(&BASE_ASSET_ID, COMMON_DEFAULT_FEE * 100).await;
+        fixture.mint_domain(SUB_DOMAIN_PART_1, 1, COMMON_DEFAULT_FEE).await.unwrap();
+        fixture.skip_n_days(420, true).await; // more than exp + grace + default decay period
+        assert_eq!(fixture.get_expired_domain_premium(SUB_DOMAIN_PART_1, &BASE_ASSET_ID).await, 0);
+        fixture.mint_domain(SUB_DOMAIN_PART_1, 1, COMMON_DEFAULT_FEE).await.unwrap();
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn test_set_zero_premium_decay_period() {
+        let fixture = setup().await;
+        fixture.set_premium_decay_period(0).await;
+    }
+
+    #[test]
+    fn test_premium_schedule() {
+        let config = PremiumConfig { start_premium: 1000, decay_period: 100 };
+        assert_eq!(premium_at(config, 500, 499), 0);
+        assert_eq!(premium_at(config, 500, 500), 1000);
+        assert_eq!(premium_at(config, 500, 550), 500);
+        assert_eq!(premium_at(config, 500, 599), 10);
+        assert_eq!(premium_at(config, 500, 600), 0);
+        let schedule = premium_schedule(config, 500, 25);
+        assert_eq!(schedule, vec![(500, 1000), (525, 750), (550, 500), (575, 250), (600, 0)]);
+    }
+
+    #[tokio::test]
+    async fn test_referral_fee_accrued_and_withdrawn() {
+        let fixture = setup().await;
+        let referrer = Identity::Address(fixture.deployer.address().into());
+        fixture.set_referral_fee(1000).await; // 10%
+        fixture.mint_domain_with_referrer(SUB_DOMAIN_PART_1, 1, COMMON_DEFAULT_FEE, Some(referrer.clone()), None).await.unwrap();
+        assert_eq!(fixture.get_referral_balance(referrer.clone(), &BASE_ASSET_ID).await, COMMON_DEFAULT_FEE / 10);
+
+        let provider = fixture.deployer.try_provider().unwrap();
+        let registrar_balance_before = provider
+            .get_contract_asset_balance(fixture.registrar_contract.contract_id(), BASE_ASSET_ID)
+            .await
+            .unwrap();
+        fixture.withdraw_referral_fees(&fixture.deployer, &BASE_ASSET_ID).await;
+        let registrar_balance_after = provider
+            .get_contract_asset_balance(fixture.registrar_contract.contract_id(), BASE_ASSET_ID)
+            .await
+            .unwrap();
+        assert_eq!(registrar_balance_before - registrar_balance_after, COMMON_DEFAULT_FEE / 10);
+        assert_eq!(fixture.get_referral_balance(referrer, &BASE_ASSET_ID).await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_owner_withdrawal_keeps_referral_fees() {
+        let fixture = setup().await;
+        let referrer = Identity::Address(fixture.deployer.address().into());
+        fixture.set_referral_fee(2000).await; // 20%
+        fixture.mint_domain_with_referrer(SUB_DOMAIN_PART_1, 1, COMMON_DEFAULT_FEE, Some(referrer.clone()), None).await.unwrap();
+        fixture.withdraw_funds(&BASE_ASSET_ID).await;
+        let registrar_balance = fixture.deployer.try_provider().unwrap()
+            .get_contract_asset_balance(fixture.registrar_contract.contract_id(), BASE_ASSET_ID)
+            .await
+            .unwrap();
+        assert_eq!(registrar_balance, COMMON_DEFAULT_FEE / 5);
+        assert_eq!(fixture.get_referral_balance(referrer, &BASE_ASSET_ID).await, COMMON_DEFAULT_FEE / 5);
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "SelfReferral")]
+    async fn test_self_referral() {
+        let fixture = setup().await;
+        let user_identity = Identity::Address(fixture.user.address().into());
+        fixture.set_referral_fee(1000).await;
+        fixture.mint_domain_with_referrer(SUB_DOMAIN_PART_1, 1, COMMON_DEFAULT_FEE, Some(user_identity), None).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_mint_with_voucher() {
+        let fixture = setup().await;
+        let user_identity = Identity::Address(fixture.user.address().into());
+        let (signer, signer_address) = voucher_signer();
+        fixture.set_voucher_signer(Some(signer_address)).await;
+        let id = random::<[u8; 32]>();
+        let expires_at = unix_to_tai64(fixture.get_timestamp().await as u64 + 3600);
+        let voucher = fixture.create_voucher(&signer, &user_identity, SUB_DOMAIN_PART_1, id, 5000, expires_at);
+
+        let price = fixture.get_domain_price_with_voucher(SUB_DOMAIN_PART_1, 1, &BASE_ASSET_ID, voucher.clone()).await;
+        assert_eq!(price, COMMON_DEFAULT_FEE / 2);
+        assert!(!fixture.is_voucher_used(id).await);
+
+        let asset = fixture.mint_domain_with_referrer(SUB_DOMAIN_PART_1, 1, price, None, Some(voucher)).await.unwrap();
+        assert_eq!(fixture.user.get_asset_balance(&asset).await.unwrap(), 1);
+        assert!(fixture.is_voucher_used(id).await);
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "VoucherAlreadyUsed")]
+    async fn test_voucher_reuse() {
+        let fixture = setup().await;
+        let user_identity = Identity::Address(fixture.user.address().into());
+        let (signer, signer_address) = voucher_signer();
+        fixture.set_voucher_signer(Some(signer_address)).await;
+        let expires_at = unix_to_tai64(fixture.get_timestamp().await as u64 + 3600);
+        let voucher = fixture.create_voucher(&signer, &user_identity, SUB_DOMAIN_PART_1, random(), 5000, expires_at);
+        fixture.mint_domain_with_referrer(SUB_DOMAIN_PART_1, 1, COMMON_DEFAULT_FEE, None, Some(voucher.clone())).await.unwrap();
+        fixture.mint_domain_with_referrer(SUB_DOMAIN_PART_2, 1, COMMON_DEFAULT_FEE, None, Some(voucher)).await.unwrap();
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "InvalidSignature")]
+    async fn test_voucher_wrong_signer() {
+        let fixture = setup().await;
+        let user_identity = Identity::Address(fixture.user.address().into());
+        let (_, signer_address) = voucher_signer();
+        let (other_signer, _) = voucher_signer();
+        fixture.set_voucher_signer(Some(signer_address)).await;
+        let expires_at = unix_to_tai64(fixture.get_timestamp().await as u64 + 3600);
+        let voucher = fixture.create_voucher(&other_signer, &user_identity, SUB_DOMAIN_PART_1, random(), 5000, expires_at);
+        fixture.mint_domain_with_referrer(SUB_DOMAIN_PART_1, 1, COMMON_DEFAULT_FEE, None, Some(voucher)).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_voucher_bound_to_buyer_and_domain() {
+        let fixture = setup().await;
+        let deployer_identity = Identity::Address(fixture.deployer.address().into());
+        let user_identity = Identity::Address(fixture.user.address().into());
+        let (signer, signer_address) = voucher_signer();
+        fixture.set_voucher_signer(Some(signer_address)).await;
+        let expires_at = unix_to_tai64(fixture.get_timestamp().await as u64 + 3600);
+
+        // a voucher issued to another buyer can't be front-run by the user
+        let voucher = fixture.create_voucher(&signer, &deployer_identity, SUB_DOMAIN_PART_1, random(), 5000, expires_at);
+        let err = fixture.mint_domain_with_referrer(SUB_DOMAIN_PART_1, 1, COMMON_DEFAULT_FEE, None, Some(voucher)).await.unwrap_err();
+        assert!(err.to_string().contains("InvalidSignature"));
+
+        // nor used for another domain
+        let voucher = fixture.create_voucher(&signer, &user_identity, SUB_DOMAIN_PART_2, random(), 5000, expires_at);
+        let err = fixture.mint_domain_with_referrer(SUB_DOMAIN_PART_1, 1, COMMON_DEFAULT_FEE, None, Some(voucher)).await.unwrap_err();
+        assert!(err.to_string().contains("InvalidSignature"));
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "VoucherExpired")]
+    async fn test_expired_voucher() {
+        let fixture = setup().await;
+        let user_identity = Identity::Address(fixture.user.address().into());
+        let (signer, signer_address) = voucher_signer();
+        fixture.set_voucher_signer(Some(signer_address)).await;
+        let expires_at = unix_to_tai64(fixture.get_timestamp().await as u64 + 3600);
+        let voucher = fixture.create_voucher(&signer, &user_identity, SUB_DOMAIN_PART_1, random(), 5000, expires_at);
+        fixture.skip_n_days(1, true).await;
+        fixture.mint_domain_with_referrer(SUB_DOMAIN_PART_1, 1, COMMON_DEFAULT_FEE, None, Some(voucher)).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_revenue_accounting() {
+        let fixture = setup().await;
+        let referrer = Identity::Address(fixture.deployer.address().into());
+        fixture.set_referral_fee(1000).await;
+        fixture.mint_domain(SUB_DOMAIN_PART_1, 1, COMMON_DEFAULT_FEE).await.unwrap();
+        fixture.mint_domain_with_referrer(SUB_DOMAIN_PART_2, 1, COMMON_DEFAULT_FEE, Some(referrer), None).await.unwrap();
+
+        let report = fixture.get_treasury_report(&BASE_ASSET_ID).await;
+        assert_eq!(report.collected_revenue, COMMON_DEFAULT_FEE * 2);
+        assert_eq!(report.withdrawn_funds, 0);
+        assert_eq!(report.pending_referral_fees, COMMON_DEFAULT_FEE / 10);
+        assert_eq!(report.pending_funds, COMMON_DEFAULT_FEE * 2 - COMMON_DEFAULT_FEE / 10);
+
+        fixture.withdraw_funds(&BASE_ASSET_ID).await;
+        let report = fixture.get_treasury_report(&BASE_ASSET_ID).await;
+        assert_eq!(report.collected_revenue, COMMON_DEFAULT_FEE * 2);
+        assert_eq!(report.withdrawn_funds, COMMON_DEFAULT_FEE * 2 - COMMON_DEFAULT_FEE / 10);
+        assert_eq!(report.pending_funds, 0);
+    }
+
+    #[tokio::test]
+    async fn test_split_withdrawal() {
+        let fixture = setup().await;
+        let deployer_identity = Identity::Address(fixture.deployer.address().into());
+        let user_identity = Identity::Address(fixture.user.address().into());
+        fixture.set_fees(&usdc_asset_id(), 1000, 100, 10).await;
+        for domain in ["abcde", "1238172", "aaaaa", "domain", "000000"].iter() {
+            fixture._mint_domain(&domain, 1, 10, Some(usdc_asset_id())).await.unwrap();
+        }
+        fixture.set_beneficiaries(vec![
+            Beneficiary { recipient: deployer_identity, share_bps: 7000 },
+            Beneficiary { recipient: user_identity, share_bps: 3000 },
+        ]).await;
+        assert_eq!(fixture.get_beneficiaries().await.len(), 2);
+
+        let deployer_balance_before = fixture.deployer.get_asset_balance(&usdc_asset_id()).await.unwrap();
+        let user_balance_before = fixture.user.get_asset_balance(&usdc_asset_id()).await.unwrap();
+        fixture.withdraw_funds(&usdc_asset_id()).await;
+        let deployer_balance_after = fixture.deployer.get_asset_balance(&usdc_asset_id()).await.unwrap();
+        let user_balance_after = fixture.user.get_asset_balance(&usdc_asset_id()).await.unwrap();
+        assert_eq!(deployer_balance_after - deployer_balance_before, 35);
+        assert_eq!(user_balance_after - user_balance_before, 15);
+        assert_eq!(fixture.get_treasury_report(&usdc_asset_id()).await.withdrawn_funds, 50);
+    }
+
+    #[tokio::test]
+    async fn test_withdraw_funds_to_recipient() {
+        let fixture = setup().await;
+        let user_identity = Identity::Address(fixture.user.address().into());
+        fixture.set_fees(&usdc_asset_id(), 1000, 100, 10).await;
+        fixture._mint_domain(SUB_DOMAIN_PART_1, 1, 10, Some(usdc_asset_id())).await.unwrap();
+        let user_balance_before = fixture.user.get_asset_balance(&usdc_asset_id()).await.unwrap();
+        fixture.withdraw_funds_to(&usdc_asset_id(), user_identity, 4).await;
+        let user_balance_after = fixture.user.get_asset_balance(&usdc_asset_id()).await.unwrap();
+        assert_eq!(user_balance_after - user_balance_before, 4);
+        let report = fixture.get_treasury_report(&usdc_asset_id()).await;
+        assert_eq!(report.withdrawn_funds, 4);
+        assert_eq!(report.pending_funds, 6);
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "InsufficientFunds")]
+    async fn test_withdraw_more_than_pending() {
+        let fixture = setup().await;
+        let user_identity = Identity::Address(fixture.user.address().into());
+        fixture.set_fees(&usdc_asset_id(), 1000, 100, 10).await;
+        fixture._mint_domain(SUB_DOMAIN_PART_1, 1, 10, Some(usdc_asset_id())).await.unwrap();
+        fixture.withdraw_funds_to(&usdc_asset_id(), user_identity, 11).await;
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "InvalidBeneficiaryShares")]
+    async fn test_invalid_beneficiary_shares() {
+        let fixture = setup().await;
+        let deployer_identity = Identity::Address(fixture.deployer.address().into());
+        fixture.set_beneficiaries(vec![
+            Beneficiary { recipient: deployer_identity, share_bps: 9000 },
+        ]).await;
+    }
+
+    #[tokio::test]
+    async fn test_mint_domain_for_recipient() {
+        let fixture = setup().await;
+        let recipient = Identity::Address(fixture.deployer.address().into());
+        let asset = fixture.mint_domain_for(recipient, SUB_DOMAIN_PART_1, 1, COMMON_DEFAULT_FEE, None).await.unwrap();
+        assert_eq!(fixture.deployer.get_asset_balance(&asset).await.unwrap(), 1);
+        assert_eq!(fixture.user.get_asset_balance(&asset).await.unwrap(), 0);
+        assert_eq!(fixture.resolve_domain(SUB_DOMAIN_1).await, None);
+    }
+
+    #[tokio::test]
+    async fn test_mint_domain_for_recipient_with_address() {
+        let fixture = setup().await;
+        let recipient = Identity::Address(fixture.deployer.address().into());
+        let asset = fixture.mint_domain_for(recipient.clone(), SUB_DOMAIN_PART_1, 1, COMMON_DEFAULT_FEE, Some(recipient.clone())).await.unwrap();
+        assert_eq!(fixture.deployer.get_asset_balance(&asset).await.unwrap(), 1);
+        assert_eq!(fixture.user.get_asset_balance(&asset).await.unwrap(), 0);
+        let registrar_nft_balance = fixture.deployer.try_provider().unwrap()
+            .get_contract_asset_balance(fixture.registrar_contract.contract_id(), asset)
+            .await
+            .unwrap();
+        assert_eq!(registrar_nft_balance, 0);
+        assert_eq!(fixture.resolve_domain(SUB_DOMAIN_1).await, Some(recipient));
+        assert_eq!(fixture.get_domain_name(asset).await, SUB_DOMAIN_1.to_string());
+    }
+
+    #[tokio::test]
+    async fn test_mint_domain_for_recipient_owns_records() {
+        let fixture = setup().await;
+        let recipient = Identity::Address(fixture.deployer.address().into());
+        fixture.mint_domain_for(recipient.clone(), SUB_DOMAIN_PART_1, 1, COMMON_DEFAULT_FEE, Some(recipient)).await.unwrap();
+
+        // the recipient and not the registrar owns the records, so its account-wide operators apply right away
+        let operator = Identity::Address(fixture.user.address().into());
+        fixture.set_approval_for_all(&fixture.deployer, operator.clone(), true).await.unwrap();
+        assert!(fixture.is_operator(SUB_DOMAIN_1, operator.clone()).await);
+        fixture.set_text_as(&fixture.user, SUB_DOMAIN_1, "url", Some("https://fuelname.com")).await.unwrap();
+        assert_eq!(fixture.get_text(SUB_DOMAIN_1, "url").await, Some("https://fuelname.com".to_string()));
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "WrongFeeAmount")]
+    async fn test_mint_domain_for_with_insufficient_fee() {
+        let fixture = setup().await;
+        let recipient = Identity::Address(fixture.deployer.address().into());
+        fixture.mint_domain_for(recipient, SUB_DOMAIN_PART_1, 2, COMMON_DEFAULT_FEE, None).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_register_full_with_primary() {
+        let fixture = setup().await;
+        let user_identity = Identity::Address(fixture.user.address().into());
+        let expected_asset = fixture.get_next_domain_asset_id(SUB_DOMAIN_1).await;
+        let state = fixture.register_full(SUB_DOMAIN_PART_1, 1, COMMON_DEFAULT_FEE, true).await.unwrap();
+        assert_eq!(state.asset_id, expected_asset);
+        assert_eq!(state.domain_name, SUB_DOMAIN_1.to_string());
+        assert!(state.expiration.is_some());
+        assert_eq!(state.resolver, Some(fixture.resolver_contract.contract_id().clone().into()));
+        assert_eq!(state.resolved_address, Some(user_identity));
+        assert_eq!(state.primary_domain, Some(state.asset_id));
+        assert_eq!(fixture.user.get_asset_balance(&state.asset_id).await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_register_full_without_primary() {
+        let fixture = setup().await;
+        let user_identity = Identity::Address(fixture.user.address().into());
+        let state = fixture.register_full(SUB_DOMAIN_PART_1, 1, COMMON_DEFAULT_FEE, false).await.unwrap();
+        assert_eq!(state.resolved_address, Some(user_identity));
+        assert_eq!(state.primary_domain, None);
+    }
+
+    #[tokio::test]
+    async fn test_register_full_is_atomic() {
+        let fixture = setup().await;
+        let result = fixture.register_full(SUB_DOMAIN_PART_1, 1, COMMON_DEFAULT_FEE - 1, true).await;
+        assert!(result.is_err());
+        let asset = fixture.get_domain_asset_id(SUB_DOMAIN_1).await;
+        assert_eq!(fixture.domain_exists(asset).await, false);
+    }
+
+    #[tokio::test]
+    async fn test_register_full_after_expiration() {
+        let fixture = setup().await;
+        let initial_asset = fixture.mint_domain(SUB_DOMAIN_PART_1, 1, COMMON_DEFAULT_FEE).await.unwrap();
+        fixture.skip_n_days(400, true).await;
+        let state = fixture.register_full(SUB_DOMAIN_PART_1, 1, COMMON_DEFAULT_FEE, true).await.unwrap();
+        assert_ne!(state.asset_id, initial_asset);
+        assert_eq!(state.primary_domain, Some(state.asset_id));
+    }
+
+    #[tokio::test]
+    async fn test_mint_domains_batch() {
+        let fixture = setup().await;
+        let domains = [("brand", 1), ("brand-app", 2), ("dao", 1)];
+        let price = fixture.get_batch_price(&domains, &BASE_ASSET_ID).await;
+        assert_eq!(price, COMMON_DEFAULT_FEE * 3 + THREE_LETTER_DEFAULT_FEE);
+        let assets = fixture.mint_domains(&domains, price).await.unwrap();
+        assert_eq!(assets.len(), 3);
+        for (asset, (domain, _)) in assets.iter().zip(domains.iter()) {
+            assert_eq!(fixture.user.get_asset_balance(asset).await.unwrap(), 1);
+            assert_eq!(fixture.get_domain_name(*asset).await, format!("{}.fuel", domain));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_mint_domains_batch_insufficient_payment() {
+        let fixture = setup().await;
+        let domains = [("brand", 1), ("brand-app", 2)];
+        let price = fixture.get_batch_price(&domains, &BASE_ASSET_ID).await;
+        assert!(fixture.mint_domains(&domains, price - 1).await.is_err());
+        let asset = fixture.get_domain_asset_id("brand.fuel").await;
+        assert_eq!(fixture.domain_exists(asset).await, false);
+    }
+
+    #[tokio::test]
+    async fn test_mint_domains_batch_with_invalid_name() {
+        let fixture = setup().await;
+        let domains = [("brand", 1), ("brand_app", 1)];
+        assert!(fixture.mint_domains(&domains, COMMON_DEFAULT_FEE * 2).await.is_err());
+        let asset = fixture.get_domain_asset_id("brand.fuel").await;
+        assert_eq!(fixture.domain_exists(asset).await, false);
+    }
+
+    #[tokio::test]
+    async fn test_mint_domains_batch_with_taken_name() {
+        let fixture = setup().await;
+        fixture.mint_domain("brand-dao", 1, COMMON_DEFAULT_FEE).await.unwrap();
+        let domains = [("brand", 1), ("brand-dao", 1)];
+        assert!(fixture.mint_domains(&domains, COMMON_DEFAULT_FEE * 2).await.is_err());
+        let asset = fixture.get_domain_asset_id("brand.fuel").await;
+        assert_eq!(fixture.domain_exists(asset).await, false);
+    }
+
+    #[tokio::test]
+    async fn test_mint_domains_batch_with_duplicates() {
+        let fixture = setup().await;
+        let domains = [("brand", 1), ("brand", 1)];
+        assert!(fixture.mint_domains(&domains, COMMON_DEFAULT_FEE * 2).await.is_err());
+        let asset = fixture.get_domain_asset_id("brand.fuel").await;
+        assert_eq!(fixture.domain_exists(asset).await, false);
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "EmptyBatch")]
+    async fn test_mint_domains_empty_batch() {
+        let fixture = setup().await;
+        fixture.mint_domains(&[], 0).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_default_period_limits() {
+        let fixture = setup().await;
+        let limits = fixture.get_period_limits().await;
+        assert_eq!(limits.min_periods, 1);
+        assert_eq!(limits.max_periods, 3);
+        assert_eq!(limits.period_duration, ONE_YEAR_SECONDS);
+        assert!(fixture.mint_domain(SUB_DOMAIN_PART_1, 4, COMMON_DEFAULT_FEE * 4).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_increase_max_years() {
+        let fixture = setup().await;
+        fixture.set_period_limits(1, 5, ONE_YEAR_SECONDS).await;
+        assert_eq!(fixture.get_domain_price(SUB_DOMAIN_PART_1, 5, &BASE_ASSET_ID).await, COMMON_DEFAULT_FEE * 5);
+        fixture.mint_domain(SUB_DOMAIN_PART_1, 5, COMMON_DEFAULT_FEE * 5).await.unwrap();
+        assert!(fixture.mint_domain(SUB_DOMAIN_PART_2, 6, COMMON_DEFAULT_FEE * 6).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_monthly_periods() {
+        let fixture = setup().await;
+        let month = ONE_YEAR_SECONDS / 12;
+        fixture.set_period_limits(1, 12, month).await;
+        let monthly_price = fixture.get_domain_price(SUB_DOMAIN_PART_1, 1, &BASE_ASSET_ID).await;
+        assert_eq!(monthly_price, COMMON_DEFAULT_FEE / 12);
+        let price = fixture.get_domain_price(SUB_DOMAIN_PART_1, 6, &BASE_ASSET_ID).await;
+        assert_eq!(price, COMMON_DEFAULT_FEE / 2);
+        fixture.mint_domain(SUB_DOMAIN_PART_1, 6, price).await.unwrap();
+        let expiration = fixture.get_domain_expiration(SUB_DOMAIN_1).await.unwrap();
+        fixture.renew_domain(SUB_DOMAIN_PART_1, 1, monthly_price).await;
+        assert_eq!(fixture.get_domain_expiration(SUB_DOMAIN_1).await.unwrap(), expiration + month);
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "InvalidPeriodLimits")]
+    async fn test_invalid_period_limits() {
+        let fixture = setup().await;
+        fixture.set_period_limits(3, 1, ONE_YEAR_SECONDS).await;
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "InvalidPeriodDuration")]
+    async fn test_period_duration_too_short() {
+        let fixture = setup().await;
+        fixture.set_period_limits(1, 12, 60).await;
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "ZeroPeriodFee")]
+    async fn test_prorated_fee_rounding_to_zero() {
+        let fixture = setup().await;
+        fixture.set_fees(&BASE_ASSET_ID, 100, 100, 100).await;
+        fixture.set_period_limits(1, 12, 86400).await;
+        fixture.get_domain_price(SUB_DOMAIN_PART_1, 1, &BASE_ASSET_ID).await;
+    }
+
+    #[tokio::test]
+    async fn test_pause_registrar_globally() {
+        let fixture = setup().await;
+        assert!(!fixture.paused(&ContractType::Registrar, None).await);
+        fixture.pause(&ContractType::Registrar, None).await;
+        assert!(fixture.paused(&ContractType::Registrar, None).await);
+        assert!(fixture.paused(&ContractType::Registrar, Some(PausableOperation::Mint)).await);
+        assert!(fixture.mint_domain(SUB_DOMAIN_PART_1, 1, COMMON_DEFAULT_FEE).await.is_err());
+
+        fixture.unpause(&ContractType::Registrar, None).await;
+        assert!(!fixture.paused(&ContractType::Registrar, None).await);
+        fixture.mint_domain(SUB_DOMAIN_PART_1, 1, COMMON_DEFAULT_FEE).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_pause_mint_only() {
+        let fixture = setup().await;
+        fixture.mint_domain(SUB_DOMAIN_PART_1, 1, COMMON_DEFAULT_FEE).await.unwrap();
+        fixture.pause(&ContractType::Registrar, Some(PausableOperation::Mint)).await;
+        assert!(!fixture.paused(&ContractType::Registrar, None).await);
+        assert!(fixture.paused(&ContractType::Registrar, Some(PausableOperation::Mint)).await);
+        assert!(!fixture.paused(&ContractType::Registrar, Some(PausableOperation::Renew)).await);
+        assert!(fixture.mint_domain(SUB_DOMAIN_PART_2, 1, COMMON_DEFAULT_FEE).await.is_err());
+        fixture.renew_domain(SUB_DOMAIN_PART_1, 1, COMMON_DEFAULT_FEE).await;
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "OperationPaused")]
+    async fn test_pause_renew() {
+        let fixture = setup().await;
+        fixture.mint_domain(SUB_DOMAIN_PART_1, 1, COMMON_DEFAULT_FEE).await.unwrap();
+        fixture.pause(&ContractType::Registrar, Some(PausableOperation::Renew)).await;
+        fixture.renew_domain(SUB_DOMAIN_PART_1, 1, COMMON_DEFAULT_FEE).await;
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "OperationPaused")]
+    async fn test_pause_set_resolver() {
+        let fixture = setup().await;
+        fixture.mint_domain(SUB_DOMAIN_PART_1, 1, COMMON_DEFAULT_FEE).await.unwrap();
+        fixture.pause(&ContractType::Registry, Some(PausableOperation::SetResolver)).await;
+        fixture.set_domain_resolver(SUB_DOMAIN_1, ContractId::new(random())).await;
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "OperationPaused")]
+    async fn test_pause_set_primary() {
+        let fixture = setup().await;
+        let user_identity = Identity::Address(fixture.user.address().into());
+        fixture.mint_domain(SUB_DOMAIN_PART_1, 1, COMMON_DEFAULT_FEE).await.unwrap();
+        fixture.set_resolution(SUB_DOMAIN_1, Some(user_identity)).await;
+        fixture.pause(&ContractType::Registry, Some(PausableOperation::SetPrimary)).await;
+        fixture.set_primary(SUB_DOMAIN_1).await;
+    }
+
+    #[tokio::test]
+    async fn test_registry_pause_blocks_minting() {
+        let fixture = setup().await;
+        fixture.pause(&ContractType::Registry, None).await;
+        assert!(fixture.mint_domain(SUB_DOMAIN_PART_1, 1, COMMON_DEFAULT_FEE).await.is_err());
+        fixture.unpause(&ContractType::Registry, None).await;
+        fixture.mint_domain(SUB_DOMAIN_PART_1, 1, COMMON_DEFAULT_FEE).await.unwrap();
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn test_pause_not_owner() {
+        let fixture = setup().await;
+        fixture.registrar_contract
+            .clone()
+            .with_account(fixture.user.clone())
+            .methods()
+            .pause(None)
+            .with_contract_ids(&[fixture.contracts.get(&ContractType::Registrar).unwrap().target_id.into()])
+            .call()
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_owner_has_all_roles() {
+        let fixture = setup().await;
+        let deployer_identity = Identity::Address(fixture.deployer.address().into());
+        for role in [Role::PricingAdmin, Role::Treasurer, Role::TldAdmin, Role::Pauser] {
+            assert!(fixture.has_role(&ContractType::Registrar, role.clone(), deployer_identity.clone()).await);
+            assert!(fixture.has_role(&ContractType::Registry, role, deployer_identity.clone()).await);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_grant_and_revoke_role() {
+        let fixture = setup().await;
+        let user_identity = Identity::Address(fixture.user.address().into());
+        assert!(!fixture.has_role(&ContractType::Registrar, Role::PricingAdmin, user_identity.clone()).await);
+
+        fixture.grant_role(&ContractType::Registrar, Role::PricingAdmin, user_identity.clone()).await;
+        assert!(fixture.has_role(&ContractType::Registrar, Role::PricingAdmin, user_identity.clone()).await);
+        assert!(!fixture.has_role(&ContractType::Registrar, Role::Treasurer, user_identity.clone()).await);
+        let gp = MIN_GRACE_PERIOD_DURATION + 1000;
+        fixture.set_grace_period_as_user(gp).await;
+        assert_eq!(fixture.get_grace_period().await, gp);
+
+        fixture.revoke_role(&ContractType::Registrar, Role::PricingAdmin, user_identity.clone()).await;
+        assert!(!fixture.has_role(&ContractType::Registrar, Role::PricingAdmin, user_identity).await);
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "MissingRole")]
+    async fn test_revoked_role_is_rejected() {
+        let fixture = setup().await;
+        let user_identity = Identity::Address(fixture.user.address().into());
+        fixture.grant_role(&ContractType::Registrar, Role::PricingAdmin, user_identity.clone()).await;
+        fixture.revoke_role(&ContractType::Registrar, Role::PricingAdmin, user_identity).await;
+        fixture.set_grace_period_as_user(MIN_GRACE_PERIOD_DURATION + 1).await;
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "MissingRole")]
+    async fn test_wrong_role_is_rejected() {
+        let fixture = setup().await;
+        let user_identity = Identity::Address(fixture.user.address().into());
+        fixture.grant_role(&ContractType::Registrar, Role::Treasurer, user_identity).await;
+        fixture.set_grace_period_as_user(MIN_GRACE_PERIOD_DURATION + 1).await;
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn test_grant_role_not_owner() {
+        let fixture = setup().await;
+        let user_identity = Identity::Address(fixture.user.address().into());
+        fixture.registrar_contract
+            .clone()
+            .with_account(fixture.user.clone())
+            .methods()
+            .grant_role(Role::PricingAdmin, user_identity)
+            .with_contract_ids(&[fixture.contracts.get(&ContractType::Registrar).unwrap().target_id.into()])
+            .call()
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_initial_roles_assigned_on_deploy() {
+        let fixture = setup_with_roles(|user| vec![
+            RoleAssignment { role: Role::Treasurer, account: Identity::Address(user.address().into()) },
+            RoleAssignment { role: Role::Pauser, account: Identity::Address(user.address().into()) },
+        ]).await;
+        let user_identity = Identity::Address(fixture.user.address().into());
+        assert!(fixture.has_role(&ContractType::Registrar, Role::Treasurer, user_identity.clone()).await);
+        assert!(!fixture.has_role(&ContractType::Registry, Role::Treasurer, user_identity.clone()).await);
+        assert!(fixture.has_role(&ContractType::Registrar, Role::Pauser, user_identity.clone()).await);
+        assert!(fixture.has_role(&ContractType::Registry, Role::Pauser, user_identity.clone()).await);
+        assert!(!fixture.has_role(&ContractType::Registrar, Role::PricingAdmin, user_identity).await);
+
+        // the treasurer can withdraw without the owner key
+        fixture.set_fees(&usdc_asset_id(), 1000, 100, 10).await;
+        fixture._mint_domain(SUB_DOMAIN_PART_1, 1, 10, Some(usdc_asset_id())).await.unwrap();
+        let balance_before = fixture.user.get_asset_balance(&usdc_asset_id()).await.unwrap();
+        fixture.registrar_contract
+            .clone()
+            .with_account(fixture.user.clone())
+            .methods()
+            .withdraw_funds(usdc_asset_id())
+            .with_contract_ids(&[fixture.contracts.get(&ContractType::Registrar).unwrap().target_id.into()])
+            .with_variable_output_policy(VariableOutputPolicy::Exactly(1))
+            .call()
+            .await
+            .unwrap();
+        let balance_after = fixture.user.get_asset_balance(&usdc_asset_id()).await.unwrap();
+        assert_eq!(balance_after - balance_before, 10);
+    }
+
+    #[tokio::test]
+    async fn test_extra_tld_registrar() {
+        let fixture = setup().await;
+        let tld_fixture = setup_extra_tld(&fixture).await;
+        assert_eq!(fixture.get_root_domain().await, HIGH_LEVEL_DOMAIN);
+        assert_eq!(tld_fixture.get_root_domain().await, EXTRA_HIGH_LEVEL_DOMAIN);
+
+        let tld_asset = fixture.get_domain_asset_id(EXTRA_HIGH_LEVEL_DOMAIN).await;
+        assert!(fixture.domain_exists(tld_asset).await);
+        assert_eq!(tld_fixture.get_domain_price(SUB_DOMAIN_PART_1, 1, &BASE_ASSET_ID).await, 100);
+
+        // the same label is available under both high-level domains
+        let extra_domain = format!("{}.{}", SUB_DOMAIN_PART_1, EXTRA_HIGH_LEVEL_DOMAIN);
+        let fuel_asset = fixture.mint_domain(SUB_DOMAIN_PART_1, 1, COMMON_DEFAULT_FEE).await.unwrap();
+        let extra_asset = tld_fixture.mint_domain(SUB_DOMAIN_PART_1, 1, 100).await.unwrap();
+        assert_ne!(fuel_asset, extra_asset);
+        assert_eq!(fixture.get_domain_name(extra_asset).await, extra_domain);
+        assert_eq!(fixture.get_domain_asset_id(&extra_domain).await, extra_asset);
+        assert_eq!(fixture.user.get_asset_balance(&extra_asset).await.unwrap(), 1);
+
+        tld_fixture.renew_domain(SUB_DOMAIN_PART_1, 1, 100).await;
+        assert!(tld_fixture.get_domain_expiration(&extra_domain).await > fixture.get_domain_expiration(SUB_DOMAIN_1).await);
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "RootDomainAlreadySet")]
+    async fn test_set_root_domain_twice() {
+        let fixture = setup().await;
+        let tld_fixture = setup_extra_tld(&fixture).await;
+        tld_fixture.set_root_domain("other").await;
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "RootDomainRegistered")]
+    async fn test_set_root_domain_on_live_registrar() {
+        let fixture = setup().await;
+        // the `.fuel` registrar never set a root domain but already holds its high-level domain
+        fixture.set_root_domain("other").await;
+    }
+
+    #[tokio::test]
+    async fn test_register_full_extra_tld() {
+        let fixture = setup().await;
+        let tld_fixture = setup_extra_tld(&fixture).await;
+        let state = tld_fixture.register_full(SUB_DOMAIN_PART_1, 1, 100, false).await.unwrap();
+        assert_eq!(state.domain_name, format!("{}.{}", SUB_DOMAIN_PART_1, EXTRA_HIGH_LEVEL_DOMAIN));
+        assert!(state.expiration.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_upgrade_extra_tld_registrar() {
+        let fixture = setup().await;
+        let tld_fixture = setup_extra_tld(&fixture).await;
+        tld_fixture.mint_domain(SUB_DOMAIN_PART_1, 1, 100).await.unwrap();
+        let proxy: ContractId = tld_fixture.registrar_contract.id().clone().into();
+
+        // a later run upgrades the registrar behind the same proxy instead of registering the domain again
+        let upgraded = setup_extra_tld_with_proxy(&fixture, Some(proxy)).await;
+        let upgraded_proxy: ContractId = upgraded.registrar_contract.id().clone().into();
+        assert_eq!(upgraded_proxy, proxy);
+        assert_eq!(upgraded.get_root_domain().await, EXTRA_HIGH_LEVEL_DOMAIN);
+        upgraded.mint_domain(SUB_DOMAIN_PART_2, 1, 100).await.unwrap();
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "set EXTRA_TLD_REGISTRAR_PROXY")]
+    async fn test_extra_tld_deployed_twice() {
+        let fixture = setup().await;
+        setup_extra_tld(&fixture).await;
+        setup_extra_tld(&fixture).await;
+    }
+
+    async fn setup_deposited_parent(fixture: &Fixture, terms: Option<SubdomainTerms>) {
+        fixture.mint_domain(SUB_DOMAIN_PART_1, 1, COMMON_DEFAULT_FEE).await.unwrap();
+        fixture.deposit_domain(&fixture.user, SUB_DOMAIN_1).await.unwrap();
+        fixture.set_subdomain_terms(&fixture.user, SUB_DOMAIN_1, terms).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_claim_subdomain() {
+        let fixture = setup().await;
+        let terms = SubdomainTerms { fee_asset: BASE_ASSET_ID, fee: 10, allowlist_only: false, options: subdomain_options(SubdomainMode::Emancipated, false) };
+        setup_deposited_parent(&fixture, Some(terms.clone())).await;
+        assert_eq!(fixture.get_subdomain_terms(SUB_DOMAIN_1).await, Some(terms));
+        assert_eq!(fixture.get_parent_owner(SUB_DOMAIN_1).await, Some(Identity::Address(fixture.user.address().into())));
+
+        let user_balance_before = fixture.user.get_asset_balance(&BASE_ASSET_ID).await.unwrap();
+        let asset = fixture.claim_subdomain(&fixture.deployer, SUB_DOMAIN_1, "alice", 15, None).await.unwrap();
+        let user_balance_after = fixture.user.get_asset_balance(&BASE_ASSET_ID).await.unwrap();
+
+        let sub_domain = format!("alice.{}", SUB_DOMAIN_1);
+        assert_eq!(fixture.get_domain_name(asset).await, sub_domain);
+        assert_eq!(fixture.deployer.get_asset_balance(&asset).await.unwrap(), 1);
+        assert_eq!(fixture.get_domain_expiration(&sub_domain).await, fixture.get_domain_expiration(SUB_DOMAIN_1).await);
+        assert_eq!(user_balance_after - user_balance_before, 10);
+    }
+
+    #[tokio::test]
+    async fn test_withdraw_deposited_domain() {
+        let fixture = setup().await;
+        setup_deposited_parent(&fixture, None).await;
+        let parent_asset = fixture.get_domain_asset_id(SUB_DOMAIN_1).await;
+        assert_eq!(fixture.user.get_asset_balance(&parent_asset).await.unwrap(), 0);
+
+        assert!(fixture.withdraw_domain(&fixture.deployer, SUB_DOMAIN_1).await.is_err());
+        fixture.withdraw_domain(&fixture.user, SUB_DOMAIN_1).await.unwrap();
+        assert_eq!(fixture.user.get_asset_balance(&parent_asset).await.unwrap(), 1);
+        assert_eq!(fixture.get_parent_owner(SUB_DOMAIN_1).await, None);
+        assert!(fixture.claim_subdomain(&fixture.deployer, SUB_DOMAIN_1, "alice", 0, None).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_redeposit_after_withdraw_resets_terms() {
+        let fixture = setup().await;
+        setup_deposited_parent(&fixture, Some(SubdomainTerms { fee_asset: BASE_ASSET_ID, fee: 0, allowlist_only: true, options: subdomain_options(SubdomainMode::Emancipated, false) })).await;
+        let user_identity = Identity::Address(fixture.user.address().into());
+        fixture.set_allowlisted(&fixture.user, SUB_DOMAIN_1, user_identity.clone(), true).await.unwrap();
+
+        // the domain is sold and deposited again by the new owner
+        fixture.withdraw_domain(&fixture.user, SUB_DOMAIN_1).await.unwrap();
+        fixture.transfer(&fixture.user, SUB_DOMAIN_1, fixture.deployer.address()).await;
+        fixture.deposit_domain(&fixture.deployer, SUB_DOMAIN_1).await.unwrap();
+        assert_eq!(fixture.get_subdomain_terms(SUB_DOMAIN_1).await, None);
+        let error = fixture.claim_subdomain(&fixture.user, SUB_DOMAIN_1, "alice", 0, None).await.unwrap_err();
+        assert!(error.to_string().contains("ClaimsClosed"));
+
+        // the allowlist of the previous owner is gone as well
+        fixture.set_subdomain_terms(&fixture.deployer, SUB_DOMAIN_1, Some(SubdomainTerms { fee_asset: BASE_ASSET_ID, fee: 0, allowlist_only: true, options: subdomain_options(SubdomainMode::Emancipated, false) })).await.unwrap();
+        let error = fixture.claim_subdomain(&fixture.user, SUB_DOMAIN_1, "alice", 0, None).await.unwrap_err();
+        assert!(error.to_string().contains("NotAllowlisted"));
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "WrongFeeAmount")]
+    async fn test_free_claim_with_coins() {
+        let fixture = setup().await;
+        setup_deposited_parent(&fixture, Some(SubdomainTerms { fee_asset: BASE_ASSET_ID, fee: 0, allowlist_only: false, options: subdomain_options(SubdomainMode::Emancipated, false) })).await;
+        fixture.claim_subdomain(&fixture.deployer, SUB_DOMAIN_1, "alice", 5, None).await.unwrap();
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "ClaimsClosed")]
+    async fn test_claim_subdomain_without_terms() {
+        let fixture = setup().await;
+        setup_deposited_parent(&fixture, None).await;
+        fixture.claim_subdomain(&fixture.deployer, SUB_DOMAIN_1, "alice", 0, None).await.unwrap();
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "WrongFeeAmount")]
+    async fn test_claim_subdomain_underpaid() {
+        let fixture = setup().await;
+        setup_deposited_parent(&fixture, Some(SubdomainTerms { fee_asset: BASE_ASSET_ID, fee: 10, allowlist_only: false, options: subdomain_options(SubdomainMode::Emancipated, false) })).await;
+        fixture.claim_subdomain(&fixture.deployer, SUB_DOMAIN_1, "alice", 9, None).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_claim_subdomain_allowlist() {
+        let fixture = setup().await;
+        setup_deposited_parent(&fixture, Some(SubdomainTerms { fee_asset: BASE_ASSET_ID, fee: 0, allowlist_only: true, options: subdomain_options(SubdomainMode::Emancipated, false) })).await;
+        let error = fixture.claim_subdomain(&fixture.deployer, SUB_DOMAIN_1, "alice", 0, None).await.unwrap_err();
+        assert!(error.to_string().contains("NotAllowlisted"));
+
+        let deployer_identity = Identity::Address(fixture.deployer.address().into());
+        assert!(fixture.set_allowlisted(&fixture.deployer, SUB_DOMAIN_1, deployer_identity.clone(), true).await.is_err());
+        fixture.set_allowlisted(&fixture.user, SUB_DOMAIN_1, deployer_identity, true).await.unwrap();
+        let asset = fixture.claim_subdomain(&fixture.deployer, SUB_DOMAIN_1, "alice", 0, None).await.unwrap();
+        assert_eq!(fixture.deployer.get_asset_balance(&asset).await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_revoke_controlled_sub_domain() {
+        let fixture = setup().await;
+        fixture.mint_domain(SUB_DOMAIN_PART_1, 1, COMMON_DEFAULT_FEE).await.unwrap();
+        let deployer_identity = Identity::Address(fixture.deployer.address().into());
+        let asset = fixture.register_sub_domain(&fixture.user, SUB_DOMAIN_1, "alice", deployer_identity.clone(), subdomain_options(SubdomainMode::Controlled, false)).await.unwrap();
+        assert_eq!(fixture.get_subdomain_options(asset).await, Some(subdomain_options(SubdomainMode::Controlled, false)));
+        assert!(fixture.domain_exists(asset).await);
+
+        // the holder of a controlled subdomain can't revoke it, the parent owner can
+        let sub_domain = format!("alice.{}", SUB_DOMAIN_1);
+        let error = fixture.revoke_sub_domain(&fixture.deployer, SUB_DOMAIN_1, "alice").await.unwrap_err();
+        assert!(error.to_string().contains("NotDomainOwner"));
+        fixture.revoke_sub_domain(&fixture.user, SUB_DOMAIN_1, "alice").await.unwrap();
+        assert!(!fixture.domain_exists(asset).await);
+        assert!(!fixture.is_domain_active(asset).await);
+        assert_eq!(fixture.get_domain_resolver(&sub_domain).await, None);
+
+        // the name can be minted again as a new generation
+        let user_identity = Identity::Address(fixture.user.address().into());
+        let new_asset = fixture.register_sub_domain(&fixture.user, SUB_DOMAIN_1, "alice", user_identity, subdomain_options(SubdomainMode::Emancipated, false)).await.unwrap();
+        assert_ne!(asset, new_asset);
+        assert_eq!(fixture.get_subdomain_options(new_asset).await, Some(subdomain_options(SubdomainMode::Emancipated, false)));
+    }
+
+    #[tokio::test]
+    async fn test_reassign_controlled_sub_domain() {
+        let fixture = setup().await;
+        fixture.mint_domain(SUB_DOMAIN_PART_1, 1, COMMON_DEFAULT_FEE).await.unwrap();
+        let deployer_identity = Identity::Address(fixture.deployer.address().into());
+        let user_identity = Identity::Address(fixture.user.address().into());
+        let sub_domain = format!("alice.{}", SUB_DOMAIN_1);
+        let asset = fixture.register_sub_domain(&fixture.user, SUB_DOMAIN_1, "alice", deployer_identity, subdomain_options(SubdomainMode::Controlled, false)).await.unwrap();
+        let expiration = fixture.get_domain_expiration(&sub_domain).await;
+
+        let new_asset = fixture.reassign_sub_domain(&fixture.user, SUB_DOMAIN_1, "alice", user_identity).await.unwrap();
+        assert_ne!(asset, new_asset);
+        assert!(!fixture.domain_exists(asset).await);
+        assert!(fixture.domain_exists(new_asset).await);
+        assert_eq!(fixture.user.get_asset_balance(&new_asset).await.unwrap(), 1);
+        assert_eq!(fixture.get_domain_asset_id(&sub_domain).await, new_asset);
+        assert_eq!(fixture.get_domain_expiration(&sub_domain).await, expiration);
+        assert_eq!(fixture.get_subdomain_options(new_asset).await, Some(subdomain_options(SubdomainMode::Controlled, false)));
+    }
+
+    #[tokio::test]
+    async fn test_reassign_not_controlled_sub_domain() {
+        let fixture = setup().await;
+        fixture.mint_domain(SUB_DOMAIN_PART_1, 1, COMMON_DEFAULT_FEE).await.unwrap();
+        let deployer_identity = Identity::Address(fixture.deployer.address().into());
+        let user_identity = Identity::Address(fixture.user.address().into());
+        fixture.register_sub_domain(&fixture.user, SUB_DOMAIN_1, "alice", deployer_identity, subdomain_options(SubdomainMode::Emancipated, false)).await.unwrap();
+
+        let error = fixture.reassign_sub_domain(&fixture.user, SUB_DOMAIN_1, "alice", user_identity.clone()).await.unwrap_err();
+        assert!(error.to_string().contains("NotControlled"));
+        let error = fixture.reassign_sub_domain(&fixture.user, SUB_DOMAIN_1, "bob", user_identity.clone()).await.unwrap_err();
+        assert!(error.to_string().contains("AssetDoesNotExist"));
+        // second-level names are minted without options and belong to the registrar
+        let error = fixture.reassign_sub_domain(&fixture.user, HIGH_LEVEL_DOMAIN, SUB_DOMAIN_PART_1, user_identity).await.unwrap_err();
+        assert!(error.to_string().contains("NotDomainOwner"));
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "NotControlled")]
+    async fn test_revoke_emancipated_sub_domain() {
+        let fixture = setup().await;
+        fixture.mint_domain(SUB_DOMAIN_PART_1, 1, COMMON_DEFAULT_FEE).await.unwrap();
+        let deployer_identity = Identity::Address(fixture.deployer.address().into());
+        fixture.register_sub_domain(&fixture.user, SUB_DOMAIN_1, "alice", deployer_identity, subdomain_options(SubdomainMode::Emancipated, false)).await.unwrap();
+        fixture.revoke_sub_domain(&fixture.user, SUB_DOMAIN_1, "alice").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_parent_controls_resolver_of_controlled_sub_domain() {
+        let fixture = setup().await;
+        fixture.mint_domain(SUB_DOMAIN_PART_1, 1, COMMON_DEFAULT_FEE).await.unwrap();
+        let deployer_identity = Identity::Address(fixture.deployer.address().into());
+        let controlled = format!("alice.{}", SUB_DOMAIN_1);
+        let emancipated = format!("bob.{}", SUB_DOMAIN_1);
+        fixture.register_sub_domain(&fixture.user, SUB_DOMAIN_1, "alice", deployer_identity.clone(), subdomain_options(SubdomainMode::Controlled, false)).await.unwrap();
+        fixture.register_sub_domain(&fixture.user, SUB_DOMAIN_1, "bob", deployer_identity, subdomain_options(SubdomainMode::Emancipated, false)).await.unwrap();
+
+        let new_resolver = ContractId::from([1u8; 32]);
+        fixture.set_trusted_resolver(new_resolver, true).await.unwrap();
+        fixture.set_domain_resolver_with_proof(&fixture.user, &controlled, SUB_DOMAIN_1, new_resolver).await.unwrap();
+        assert_eq!(fixture.get_domain_resolver(&controlled).await, Some(new_resolver));
+        assert!(fixture.set_domain_resolver_with_proof(&fixture.user, &emancipated, SUB_DOMAIN_1, new_resolver).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_revoke_claimed_controlled_subdomain() {
+        let fixture = setup().await;
+        setup_deposited_parent(&fixture, Some(SubdomainTerms { fee_asset: BASE_ASSET_ID, fee: 0, allowlist_only: false, options: subdomain_options(SubdomainMode::Controlled, false) })).await;
+        let asset = fixture.claim_subdomain(&fixture.deployer, SUB_DOMAIN_1, "alice", 0, None).await.unwrap();
+        assert!(fixture.revoke_claimed_subdomain(&fixture.deployer, SUB_DOMAIN_1, "alice").await.is_err());
+        fixture.revoke_claimed_subdomain(&fixture.user, SUB_DOMAIN_1, "alice").await.unwrap();
+        assert!(!fixture.domain_exists(asset).await);
+    }
+
+    #[tokio::test]
+    async fn test_inherited_expiration_follows_parent_renewal() {
+        let fixture = setup().await;
+        fixture.mint_domain(SUB_DOMAIN_PART_1, 1, COMMON_DEFAULT_FEE).await.unwrap();
+        let deployer_identity = Identity::Address(fixture.deployer.address().into());
+        let inheriting = format!("alice.{}", SUB_DOMAIN_1);
+        let fixed = format!("bob.{}", SUB_DOMAIN_1);
+        let asset = fixture.register_sub_domain(&fixture.user, SUB_DOMAIN_1, "alice", deployer_identity.clone(), subdomain_options(SubdomainMode::Emancipated, true)).await.unwrap();
+        fixture.register_sub_domain(&fixture.user, SUB_DOMAIN_1, "bob", deployer_identity, subdomain_options(SubdomainMode::Emancipated, false)).await.unwrap();
+        assert_eq!(fixture.get_subdomain_options(asset).await, Some(subdomain_options(SubdomainMode::Emancipated, true)));
+
+        let parent_expiration = fixture.get_domain_expiration(SUB_DOMAIN_1).await;
+        assert_eq!(fixture.get_domain_expiration(&inheriting).await, parent_expiration);
+        assert_eq!(fixture.get_domain_expiration(&fixed).await, parent_expiration);
+
+        fixture.renew_domain(SUB_DOMAIN_PART_1, 1, COMMON_DEFAULT_FEE).await;
+        let renewed_expiration = fixture.get_domain_expiration(SUB_DOMAIN_1).await;
+        assert!(renewed_expiration > parent_expiration);
+        assert_eq!(fixture.get_domain_expiration(&inheriting).await, renewed_expiration);
+        assert_eq!(fixture.get_domain_expiration(&fixed).await, parent_expiration);
+
+        // past the original expiration only the inheriting subdomain is still active
+        fixture.skip_n_days(400, true).await;
+        assert!(fixture.is_domain_active(asset).await);
+        let fixed_asset = fixture.get_domain_asset_id(&fixed).await;
+        assert!(!fixture.is_domain_active(fixed_asset).await);
+    }
+
+    #[tokio::test]
+    async fn test_inherited_expiration_ends_with_parent() {
+        let fixture = setup().await;
+        fixture.mint_domain(SUB_DOMAIN_PART_1, 1, COMMON_DEFAULT_FEE).await.unwrap();
+        let deployer_identity = Identity::Address(fixture.deployer.address().into());
+        let child = fixture.register_sub_domain(&fixture.user, SUB_DOMAIN_1, "alice", deployer_identity.clone(), subdomain_options(SubdomainMode::Emancipated, true)).await.unwrap();
+        let grandchild = fixture.register_sub_domain(&fixture.deployer, &format!("alice.{}", SUB_DOMAIN_1), "app", deployer_identity, subdomain_options(SubdomainMode::Emancipated, true)).await.unwrap();
+        assert!(fixture.is_domain_active(grandchild).await);
+
+        // the parent is inactive but still in its grace period
+        fixture.skip_n_days(370, true).await;
+        assert!(!fixture.is_domain_active(child).await);
+        assert!(!fixture.is_domain_active(grandchild).await);
+        assert!(fixture.domain_exists(grandchild).await);
+
+        fixture.skip_n_days(40, true).await;
+        assert!(!fixture.domain_exists(child).await);
+        assert!(!fixture.domain_exists(grandchild).await);
+    }
+
+    #[tokio::test]
+    async fn test_self_renew_nested_sub_domains() {
+        let fixture = setup().await;
+        fixture.mint_domain(SUB_DOMAIN_PART_1, 1, COMMON_DEFAULT_FEE).await.unwrap();
+        let deployer_identity = Identity::Address(fixture.deployer.address().into());
+        let user_identity = Identity::Address(fixture.user.address().into());
+        let alice = format!("alice.{}", SUB_DOMAIN_1);
+        let app = format!("app.{}", alice);
+        let expiration = fixture.get_domain_expiration(SUB_DOMAIN_1).await.unwrap();
+        fixture.register_sub_domain(&fixture.user, SUB_DOMAIN_1, "alice", deployer_identity, subdomain_options(SubdomainMode::Emancipated, false)).await.unwrap();
+        fixture.register_sub_domain(&fixture.deployer, &alice, "app", user_identity, subdomain_options(SubdomainMode::Emancipated, false)).await.unwrap();
+        assert_eq!(fixture.get_domain_expiration(&app).await, Some(expiration));
+
+        // no approval yet
+        let error = fixture.self_renew_sub_domain(&fixture.user, &app, expiration + 100).await.unwrap_err();
+        assert!(error.to_string().contains("RenewalNotApproved"));
+
+        fixture.set_renewal_limit(&fixture.user, SUB_DOMAIN_1, "alice", Some(expiration + 1000)).await.unwrap();
+        fixture.set_renewal_limit(&fixture.deployer, &alice, "app", Some(expiration + 500)).await.unwrap();
+        assert_eq!(fixture.get_renewal_limit(&alice).await, Some(expiration + 1000));
+        assert_eq!(fixture.get_renewal_limit(&app).await, Some(expiration + 500));
+
+        // only the parent owner approves renewals
+        let error = fixture.set_renewal_limit(&fixture.user, &alice, "app", Some(expiration + 5000)).await.unwrap_err();
+        assert!(error.to_string().contains("NotDomainOwner"));
+
+        fixture.self_renew_sub_domain(&fixture.deployer, &alice, expiration + 1000).await.unwrap();
+        fixture.self_renew_sub_domain(&fixture.user, &app, expiration + 500).await.unwrap();
+        assert_eq!(fixture.get_domain_expiration(&alice).await, Some(expiration + 1000));
+        assert_eq!(fixture.get_domain_expiration(&app).await, Some(expiration + 500));
+
+        let error = fixture.self_renew_sub_domain(&fixture.user, &app, expiration + 501).await.unwrap_err();
+        assert!(error.to_string().contains("RenewalLimitExceeded"));
+        // the holder of the parent can't use the approval of the child
+        let error = fixture.self_renew_sub_domain(&fixture.deployer, &app, expiration + 500).await.unwrap_err();
+        assert!(error.to_string().contains("NotDomainOwner"));
+
+        fixture.set_renewal_limit(&fixture.deployer, &alice, "app", None).await.unwrap();
+        assert_eq!(fixture.get_renewal_limit(&app).await, None);
+    }
+
+    #[tokio::test]
+    async fn test_get_subdomains() {
+        let fixture = setup().await;
+        fixture.mint_domain(SUB_DOMAIN_PART_1, 1, COMMON_DEFAULT_FEE).await.unwrap();
+        let deployer_identity = Identity::Address(fixture.deployer.address().into());
+        let user_identity = Identity::Address(fixture.user.address().into());
+        for name in ["alice", "bob", "carol"] {
+            fixture.register_sub_domain(&fixture.user, SUB_DOMAIN_1, name, deployer_identity.clone(), subdomain_options(SubdomainMode::Controlled, false)).await.unwrap();
+        }
+        fixture.register_sub_domain(&fixture.deployer, &format!("alice.{}", SUB_DOMAIN_1), "app", user_identity.clone(), subdomain_options(SubdomainMode::Emancipated, false)).await.unwrap();
+
+        assert_eq!(fixture.get_subdomain_count(SUB_DOMAIN_1).await, 3);
+        assert_eq!(fixture.get_subdomains(SUB_DOMAIN_1, 0, 2).await.len(), 2);
+        assert_eq!(fixture.get_subdomains(SUB_DOMAIN_1, 2, 2).await.len(), 1);
+        assert_eq!(fixture.get_subdomains(SUB_DOMAIN_1, 5, 2).await.len(), 0);
+        assert_eq!(
+            fixture.list_subdomains(SUB_DOMAIN_1, 2).await.unwrap(),
+            vec![format!("alice.{}", SUB_DOMAIN_1), format!("bob.{}", SUB_DOMAIN_1), format!("carol.{}", SUB_DOMAIN_1)],
+        );
+        assert_eq!(fixture.list_subdomains(&format!("alice.{}", SUB_DOMAIN_1), 10).await.unwrap(), vec![format!("app.alice.{}", SUB_DOMAIN_1)]);
+        // pages above the registry cap are clamped and empty pages are rejected
+        assert_eq!(fixture.list_subdomains(SUB_DOMAIN_1, 1000).await.unwrap().len(), 3);
+        assert!(fixture.list_subdomains(SUB_DOMAIN_1, 0).await.is_err());
+        assert!(fixture.get_subdomains(SUB_DOMAIN_1, u64::MAX, 2).await.is_empty());
+
+        // replaced and revoked generations are skipped
+        let new_bob = fixture.reassign_sub_domain(&fixture.user, SUB_DOMAIN_1, "bob", user_identity).await.unwrap();
+        fixture.revoke_sub_domain(&fixture.user, SUB_DOMAIN_1, "carol").await.unwrap();
+        assert_eq!(fixture.get_subdomain_count(SUB_DOMAIN_1).await, 4);
+        assert_eq!(
+            fixture.list_subdomains(SUB_DOMAIN_1, 2).await.unwrap(),
+            vec![format!("alice.{}", SUB_DOMAIN_1), format!("bob.{}", SUB_DOMAIN_1)],
+        );
+        assert_eq!(fixture.get_subdomains(SUB_DOMAIN_1, 0, 10).await[1], new_bob);
+    }
+
+    #[tokio::test]
+    async fn test_subdomains_of_new_parent_generation() {
+        let fixture = setup().await;
+        fixture.mint_domain(SUB_DOMAIN_PART_1, 1, COMMON_DEFAULT_FEE).await.unwrap();
+        let deployer_identity = Identity::Address(fixture.deployer.address().into());
+        fixture.register_sub_domain(&fixture.user, SUB_DOMAIN_1, "alice", deployer_identity, subdomain_options(SubdomainMode::Emancipated, false)).await.unwrap();
+        assert_eq!(fixture.get_subdomain_count(SUB_DOMAIN_1).await, 1);
+
+        // the index belongs to the parent generation, a re-minted parent starts empty
+        fixture.skip_n_days(400, true).await;
+        fixture.mint_domain(SUB_DOMAIN_PART_1, 1, COMMON_DEFAULT_FEE).await.unwrap();
+        assert_eq!(fixture.get_subdomain_count(SUB_DOMAIN_1).await, 0);
+        assert!(fixture.list_subdomains(SUB_DOMAIN_1, 10).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_text_records() {
+        let fixture = setup().await;
+        fixture.mint_domain(SUB_DOMAIN_PART_1, 1, COMMON_DEFAULT_FEE).await.unwrap();
+        assert!(fixture.get_text_records(SUB_DOMAIN_1, &[]).await.is_empty());
+
+        fixture.set_text(SUB_DOMAIN_1, "avatar", Some("https://fuelname.com/avatar.png")).await.unwrap();
+        fixture.set_text(SUB_DOMAIN_1, "com.github", Some("fuelname")).await.unwrap();
+        fixture.set_text(SUB_DOMAIN_1, "custom", Some("value")).await.unwrap();
+        assert_eq!(fixture.get_text(SUB_DOMAIN_1, "com.github").await, Some("fuelname".to_string()));
+        assert_eq!(fixture.get_text(SUB_DOMAIN_1, "custom").await, Some("value".to_string()));
+        let records = fixture.get_text_records(SUB_DOMAIN_1, &[]).await;
+        assert_eq!(records.len(), 2);
+        assert_eq!(records.get("avatar"), Some(&"https://fuelname.com/avatar.png".to_string()));
+        // custom keys can't be enumerated, they are read when passed explicitly
+        let records = fixture.get_text_records(SUB_DOMAIN_1, &["custom", "unknown"]).await;
+        assert_eq!(records.len(), 3);
+        assert_eq!(records.get("custom"), Some(&"value".to_string()));
+
+        fixture.set_text(SUB_DOMAIN_1, "avatar", None).await.unwrap();
+        assert_eq!(fixture.get_text(SUB_DOMAIN_1, "avatar").await, None);
+        assert!(fixture.set_text(SUB_DOMAIN_1, "", Some("value")).await.unwrap_err().to_string().contains("EmptyKey"));
+        // only the holder of the NFT can edit records
+        fixture.mint_domain(SUB_DOMAIN_PART_2, 1, COMMON_DEFAULT_FEE).await.unwrap();
+        fixture.transfer(&fixture.user, SUB_DOMAIN_2, fixture.deployer.address()).await;
+        let error = fixture.set_text(SUB_DOMAIN_2, "url", Some("https://fuelname.com")).await.unwrap_err();
+        assert!(error.to_string().contains("NotDomainOwner"));
+    }
+
+    #[test]
+    fn test_address_codec() {
+        let evm = "0x314159265dd8dbb310642f98f50c066173c1259b";
+        let evm_bytes = encode_address(COIN_TYPE_ETH, evm).unwrap();
+        assert_eq!(evm_bytes.len(), 20);
+        assert_eq!(decode_address(COIN_TYPE_ETH, &evm_bytes).unwrap(), evm);
+        assert_eq!(
+            decode_address(COIN_TYPE_ETH, &encode_address(COIN_TYPE_ETH, "0x314159265DD8dbb310642f98f50C066173C1259B").unwrap()).unwrap(),
+            evm,
+        );
+        assert_eq!(encode_address(COIN_TYPE_ETH, "314159265dd8dbb310642f98f50c066173c1259b"), Err(AddressCodecError::InvalidHex));
+        assert_eq!(encode_address(COIN_TYPE_ETH, "0x3141"), Err(AddressCodecError::InvalidLength { expected: 20, actual: 2 }));
+
+        // output scripts as stored by ENS
+        let bitcoin = [
+            ("1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa", "76a91462e907b15cbf27d5425399ebf6f0fb50ebb88f1888ac"),
+            ("3Ai1JZ8pdJb2ksieUV8FsxSNVJCpoPi8W6", "a91462e907b15cbf27d5425399ebf6f0fb50ebb88f1887"),
+            ("bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4", "0014751e76e8199196d454941c45d1b3a323f1433bd6"),
+            (
+                "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqzk5jj0",
+                "512079be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798",
+            ),
+        ];
+        for (address, script) in bitcoin {
+            let bytes = encode_address(COIN_TYPE_BTC, address).unwrap();
+            assert_eq!(bytes, hex_to_bytes(script));
+            assert_eq!(decode_address(COIN_TYPE_BTC, &bytes).unwrap(), address);
+        }
+        assert_eq!(encode_address(COIN_TYPE_BTC, "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNb"), Err(AddressCodecError::InvalidChecksum));
+        assert_eq!(encode_address(COIN_TYPE_BTC, "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t5"), Err(AddressCodecError::InvalidChecksum));
+
+        for address in ["11111111111111111111111111111111", "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v"] {
+            let bytes = encode_address(COIN_TYPE_SOL, address).unwrap();
+            assert_eq!(bytes.len(), 32);
+            assert_eq!(decode_address(COIN_TYPE_SOL, &bytes).unwrap(), address);
+        }
+        assert_eq!(encode_address(COIN_TYPE_SOL, "0OIl"), Err(AddressCodecError::InvalidBase58));
+        assert_eq!(encode_address(2, evm), Err(AddressCodecError::UnsupportedCoinType(2)));
+    }
+
+    #[tokio::test]
+    async fn test_multichain_addr_records() {
+        let fixture = setup().await;
+        fixture.mint_domain(SUB_DOMAIN_PART_1, 1, COMMON_DEFAULT_FEE).await.unwrap();
+        let evm = "0x314159265dd8dbb310642f98f50c066173c1259b";
+        let bitcoin = "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4";
+
+        fixture.set_addr(SUB_DOMAIN_1, COIN_TYPE_ETH, Some(evm)).await.unwrap();
+        fixture.set_addr(SUB_DOMAIN_1, COIN_TYPE_BTC, Some(bitcoin)).await.unwrap();
+        assert_eq!(fixture.get_addr(SUB_DOMAIN_1, COIN_TYPE_ETH).await.unwrap(), Some(evm.to_string()));
+        assert_eq!(fixture.get_addr(SUB_DOMAIN_1, COIN_TYPE_BTC).await.unwrap(), Some(bitcoin.to_string()));
+        assert_eq!(fixture.get_addr(SUB_DOMAIN_1, COIN_TYPE_SOL).await.unwrap(), None);
+
+        // invalid addresses are rejected before submission
+        assert!(fixture.set_addr(SUB_DOMAIN_1, COIN_TYPE_SOL, Some(evm)).await.is_err());
+
+        fixture.set_addr(SUB_DOMAIN_1, COIN_TYPE_ETH, None).await.unwrap();
+        assert_eq!(fixture.get_addr_bytes(SUB_DOMAIN_1, COIN_TYPE_ETH).await, None);
+
+        // the resolver stores any bytes, undecodable ones are reported instead of panicking
+        fixture.multicall(SUB_DOMAIN_1, vec![RecordUpdate::Addr((COIN_TYPE_ETH, Some(Bytes(vec![1, 2, 3]))))]).await.unwrap();
+        assert_eq!(fixture.get_addr_bytes(SUB_DOMAIN_1, COIN_TYPE_ETH).await, Some(vec![1, 2, 3]));
+        assert!(fixture.get_addr(SUB_DOMAIN_1, COIN_TYPE_ETH).await.is_err());
+    }
+
+    #[test]
+    fn test_contenthash_codec() {
+        let uris = [
+            ("ipfs://bafybeibj6lixxzqtsb45ysdjnupvqkufgdvzqbnvmhw2kf7cfkesy7r7d4", "e3010170122029f2d17be6139079dc48696d1f582a8530eb9805b561eda517e22a892c7e3f1f"),
+            ("ipns://k51qzi5uqu5diikpgzudf9t3i4453xk2f8uju0c65gxt3dxw8eoku2ammlaahd", "e50101720024080112205d9a3b8f38d3b1e4c5b2f6a7d8c9e0f1a2b3c4d5e6f708192a3b4c5d6e7f8091"),
+            ("ar://Y0wmQ8BfAXcmQyzt2P0_yOyRcW-hiQ4ySW6b7Nf6LRI", "90b2ca05634c2643c05f017726432cedd8fd3fc8ec91716fa1890e32496e9becd7fa2d12"),
+        ];
+        for (uri, hash) in uris {
+            let bytes = encode_contenthash(uri).unwrap();
+            assert_eq!(bytes, hex_to_bytes(hash));
+            assert_eq!(decode_contenthash(&bytes).unwrap(), uri);
+        }
+
+        // CIDv0 is stored as the equivalent CIDv1
+        assert_eq!(
+            encode_contenthash("ipfs://QmRAQB6YaCyidP37UdDnjFY5vQuiBrcqdyoW1CuDgwxkD4").unwrap(),
+            hex_to_bytes(uris[0].1),
+        );
+        assert_eq!(encode_contenthash("https://fuelname.com"), Err(ContenthashError::UnsupportedScheme));
+        assert_eq!(encode_contenthash("ipfs://bafybeibj6lixxzqtsb45ysdjnupvqkuf"), Err(ContenthashError::InvalidCid));
+        assert_eq!(encode_contenthash("ipfs://Qm0"), Err(ContenthashError::InvalidEncoding));
+        assert_eq!(encode_contenthash("ar://Y0wmQ8BfAXcmQyzt2P0"), Err(ContenthashError::InvalidArweaveId));
+        assert_eq!(decode_contenthash(&[0xe4, 0x01]), Err(ContenthashError::UnsupportedNamespace(0xe4)));
+    }
+
+    #[tokio::test]
+    async fn test_contenthash_records() {
+        let fixture = setup().await;
+        fixture.mint_domain(SUB_DOMAIN_PART_1, 1, COMMON_DEFAULT_FEE).await.unwrap();
+        assert_eq!(fixture.get_contenthash(SUB_DOMAIN_1).await.unwrap(), None);
+
+        let uri = "ipfs://bafybeibj6lixxzqtsb45ysdjnupvqkufgdvzqbnvmhw2kf7cfkesy7r7d4";
+        fixture.set_contenthash(SUB_DOMAIN_1, Some(uri)).await.unwrap();
+        assert_eq!(fixture.get_contenthash(SUB_DOMAIN_1).await.unwrap(), Some(uri.to_string()));
+        assert!(fixture.set_contenthash(SUB_DOMAIN_1, Some("https://fuelname.com")).await.is_err());
+
+        fixture.set_contenthash(SUB_DOMAIN_1, None).await.unwrap();
+        assert_eq!(fixture.get_contenthash(SUB_DOMAIN_1).await.unwrap(), None);
+
+        // the resolver stores any bytes, undecodable ones are reported instead of panicking
+        fixture.multicall(SUB_DOMAIN_1, vec![RecordUpdate::Contenthash(Some(Bytes(vec![0xe4, 0x01])))]).await.unwrap();
+        assert_eq!(fixture.get_contenthash_bytes(SUB_DOMAIN_1).await, Some(vec![0xe4, 0x01]));
+        assert!(fixture.get_contenthash(SUB_DOMAIN_1).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_clear_records() {
+        let fixture = setup().await;
+        fixture.mint_domain(SUB_DOMAIN_PART_1, 1, COMMON_DEFAULT_FEE).await.unwrap();
+        let user_identity = Identity::Address(fixture.user.address().into());
+        fixture.set_resolution(SUB_DOMAIN_1, Some(user_identity.clone())).await;
+        fixture.set_text(SUB_DOMAIN_1, "url", Some("https://fuelname.com")).await.unwrap();
+        fixture.set_addr(SUB_DOMAIN_1, COIN_TYPE_ETH, Some("0x314159265dd8dbb310642f98f50c066173c1259b")).await.unwrap();
+        assert_eq!(fixture.get_record_version(SUB_DOMAIN_1).await, 0);
+
+        fixture.clear_records(SUB_DOMAIN_1).await.unwrap();
+        assert_eq!(fixture.get_record_version(SUB_DOMAIN_1).await, 1);
+        assert_eq!(fixture.resolve_domain(SUB_DOMAIN_1).await, None);
+        assert_eq!(fixture.get_text(SUB_DOMAIN_1, "url").await, None);
+        assert_eq!(fixture.get_addr(SUB_DOMAIN_1, COIN_TYPE_ETH).await.unwrap(), None);
+
+        // records of the new version are readable again
+        fixture.set_resolution(SUB_DOMAIN_1, Some(user_identity.clone())).await;
+        fixture.set_text(SUB_DOMAIN_1, "url", Some("https://fuelname.com/new")).await.unwrap();
+        assert_eq!(fixture.resolve_domain(SUB_DOMAIN_1).await, Some(user_identity));
+        assert_eq!(fixture.get_text(SUB_DOMAIN_1, "url").await, Some("https://fuelname.com/new".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_transfer_domain_through_registry() {
+        let fixture = setup().await;
+        fixture.mint_domain(SUB_DOMAIN_PART_1, 1, COMMON_DEFAULT_FEE).await.unwrap();
+        fixture.mint_domain(SUB_DOMAIN_PART_2, 1, COMMON_DEFAULT_FEE).await.unwrap();
+        let user_identity = Identity::Address(fixture.user.address().into());
+        for domain in [SUB_DOMAIN_1, SUB_DOMAIN_2] {
+            fixture.set_resolution(domain, Some(user_identity.clone())).await;
+            fixture.set_text(domain, "email", Some("user@fuelname.com")).await.unwrap();
+        }
+
+        fixture.transfer_domain(&fixture.user, SUB_DOMAIN_1, fixture.deployer.address(), true).await.unwrap();
+        let asset_id = fixture.get_domain_asset_id(SUB_DOMAIN_1).await;
+        assert_eq!(fixture.deployer.get_asset_balance(&asset_id).await.unwrap(), 1);
+        assert_eq!(fixture.resolve_domain(SUB_DOMAIN_1).await, None);
+        assert_eq!(fixture.get_text(SUB_DOMAIN_1, "email").await, None);
+
+        // without the signal the records of the previous owner stay
+        fixture.transfer_domain(&fixture.user, SUB_DOMAIN_2, fixture.deployer.address(), false).await.unwrap();
+        let asset_id = fixture.get_domain_asset_id(SUB_DOMAIN_2).await;
+        assert_eq!(fixture.deployer.get_asset_balance(&asset_id).await.unwrap(), 1);
+        assert_eq!(fixture.resolve_domain(SUB_DOMAIN_2).await, Some(user_identity));
+        assert_eq!(fixture.get_record_version(SUB_DOMAIN_2).await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_checked_ignores_expired_domains() {
+        let fixture = setup().await;
+        fixture.mint_domain(SUB_DOMAIN_PART_1, 1, COMMON_DEFAULT_FEE).await.unwrap();
+        let user_identity = Identity::Address(fixture.user.address().into());
+        fixture.set_resolution(SUB_DOMAIN_1, Some(user_identity.clone())).await;
+        assert_eq!(fixture.resolve_domain(SUB_DOMAIN_1).await, Some(user_identity.clone()));
+
+        // expired but still in the grace period
+        fixture.skip_n_days(380, true).await;
+        assert_eq!(fixture.resolve_domain(SUB_DOMAIN_1).await, None);
+        assert_eq!(fixture.resolve_domain_unchecked(SUB_DOMAIN_1).await, Some(user_identity.clone()));
+
+        fixture.renew_domain(SUB_DOMAIN_PART_1, 1, COMMON_DEFAULT_FEE).await;
+        assert_eq!(fixture.resolve_domain(SUB_DOMAIN_1).await, Some(user_identity));
+    }
+
+    #[tokio::test]
+    async fn test_domain_operator() {
+        let fixture = setup().await;
+        fixture.mint_domain(SUB_DOMAIN_PART_1, 1, COMMON_DEFAULT_FEE).await.unwrap();
+        let operator = Identity::Address(fixture.deployer.address().into());
+        assert!(fixture.set_text_as(&fixture.deployer, SUB_DOMAIN_1, "url", Some("https://fuelname.com")).await.is_err());
+
+        fixture.set_operator(&fixture.user, SUB_DOMAIN_1, operator.clone(), true).await.unwrap();
+        assert!(fixture.is_operator(SUB_DOMAIN_1, operator.clone()).await);
+        fixture.set_text_as(&fixture.deployer, SUB_DOMAIN_1, "url", Some("https://fuelname.com")).await.unwrap();
+        assert_eq!(fixture.get_text(SUB_DOMAIN_1, "url").await, Some("https://fuelname.com".to_string()));
+        let resolver: ContractId = fixture.resolver_contract.id().clone().into();
+        fixture.set_domain_resolver_with_proof(&fixture.deployer, SUB_DOMAIN_1, SUB_DOMAIN_1, resolver).await.unwrap();
+
+        // operators can't appoint other operators
+        let other = Identity::Address(Address::from([7u8; 32]));
+        let error = fixture.set_operator(&fixture.deployer, SUB_DOMAIN_1, other.clone(), true).await.unwrap_err();
+        assert!(error.to_string().contains("NotDomainOwner"));
+        assert!(!fixture.is_operator(SUB_DOMAIN_1, other).await);
+
+        // and are revoked with the records
+        let user_identity = Identity::Address(fixture.user.address().into());
+        fixture.clear_records(SUB_DOMAIN_1).await.unwrap();
+        assert!(!fixture.is_operator(SUB_DOMAIN_1, operator.clone()).await);
+        assert!(fixture.set_text_as(&fixture.deployer, SUB_DOMAIN_1, "url", None).await.is_err());
+        let error = fixture.set_domain_resolver_with_proof(&fixture.deployer, SUB_DOMAIN_1, SUB_DOMAIN_1, resolver).await.unwrap_err();
+        assert!(error.to_string().contains("NotDomainOwner"));
+        assert!(!fixture.is_operator(SUB_DOMAIN_1, user_identity).await);
+    }
+
+    #[tokio::test]
+    async fn test_operators_end_with_plain_transfer() {
+        let fixture = setup().await;
+        fixture.mint_domain(SUB_DOMAIN_PART_1, 1, COMMON_DEFAULT_FEE).await.unwrap();
+        let operator = Identity::Address(fixture.deployer.address().into());
+        fixture.set_operator(&fixture.user, SUB_DOMAIN_1, operator.clone(), true).await.unwrap();
+        fixture.set_approval_for_all(&fixture.user, operator.clone(), true).await.unwrap();
+        fixture.set_text_as(&fixture.deployer, SUB_DOMAIN_1, "url", Some("https://fuelname.com")).await.unwrap();
+
+        // the NFT is sent directly, without the registry
+        let buyer = WalletUnlocked::new_random(Some(fixture.user.provider().unwrap().clone()));
+        fixture.user.transfer(buyer.address(), 100_000_000, BASE_ASSET_ID, TxPolicies::default()).await.unwrap();
+        fixture.transfer(&fixture.user, SUB_DOMAIN_1, buyer.address()).await;
+
+        // the old operator loses records and resolver with the first proof of the buyer, without clearing the records
+        fixture.set_text_as(&buyer, SUB_DOMAIN_1, "avatar", Some("https://fuelname.com/avatar.png")).await.unwrap();
+        assert_eq!(fixture.get_text(SUB_DOMAIN_1, "url").await, Some("https://fuelname.com".to_string()));
+        assert!(!fixture.is_operator(SUB_DOMAIN_1, operator.clone()).await);
+        let error = fixture.set_text_as(&fixture.deployer, SUB_DOMAIN_1, "url", None).await.unwrap_err();
+        assert!(error.to_string().contains("NotDomainOwner"));
+        let resolver: ContractId = fixture.resolver_contract.id().clone().into();
+        let error = fixture.set_domain_resolver_with_proof(&fixture.deployer, SUB_DOMAIN_1, SUB_DOMAIN_1, resolver).await.unwrap_err();
+        assert!(error.to_string().contains("NotDomainOwner"));
+    }
+
+    #[tokio::test]
+    async fn test_account_wide_operator() {
+        let fixture = setup().await;
+        fixture.mint_domain(SUB_DOMAIN_PART_1, 1, COMMON_DEFAULT_FEE).await.unwrap();
+        fixture.mint_domain(SUB_DOMAIN_PART_2, 1, COMMON_DEFAULT_FEE).await.unwrap();
+        let user_identity = Identity::Address(fixture.user.address().into());
+        let operator = Identity::Address(fixture.deployer.address().into());
+        fixture.set_approval_for_all(&fixture.user, operator.clone(), true).await.unwrap();
+        assert!(fixture.is_approved_for_all(user_identity.clone(), operator.clone()).await);
+
+        // only domains the user has proven to hold are covered
+        fixture.set_text(SUB_DOMAIN_1, "avatar", Some("https://fuelname.com/avatar.png")).await.unwrap();
+        assert!(fixture.is_operator(SUB_DOMAIN_1, operator.clone()).await);
+        assert!(!fixture.is_operator(SUB_DOMAIN_2, operator.clone()).await);
+        fixture.set_text_as(&fixture.deployer, SUB_DOMAIN_1, "avatar", None).await.unwrap();
+        assert!(fixture.set_text_as(&fixture.deployer, SUB_DOMAIN_2, "avatar", Some("value")).await.is_err());
+
+        fixture.set_approval_for_all(&fixture.user, operator.clone(), false).await.unwrap();
+        assert!(!fixture.is_operator(SUB_DOMAIN_1, operator.clone()).await);
+        assert!(fixture.set_text_as(&fixture.deployer, SUB_DOMAIN_1, "avatar", Some("value")).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_resolver_multicall() {
+        let fixture = setup().await;
+        fixture.mint_domain(SUB_DOMAIN_PART_1, 1, COMMON_DEFAULT_FEE).await.unwrap();
+        let user_identity = Identity::Address(fixture.user.address().into());
+        let evm = "0x314159265dd8dbb310642f98f50c066173c1259b";
+        let uri = "ipfs://bafybeibj6lixxzqtsb45ysdjnupvqkufgdvzqbnvmhw2kf7cfkesy7r7d4";
+        let updates = RecordUpdates::new()
+            .address(Some(user_identity.clone()))
+            .text("url", Some("https://fuelname.com"))
+            .text("com.github", Some("fuelname"))
+            .addr(COIN_TYPE_ETH, Some(evm))
+            .unwrap()
+            .contenthash(Some(uri))
+            .unwrap()
+            .build();
+        fixture.multicall(SUB_DOMAIN_1, updates).await.unwrap();
+        assert_eq!(fixture.resolve_domain(SUB_DOMAIN_1).await, Some(user_identity.clone()));
+        assert_eq!(fixture.get_text_records(SUB_DOMAIN_1, &[]).await.len(), 2);
+        assert_eq!(fixture.get_addr(SUB_DOMAIN_1, COIN_TYPE_ETH).await.unwrap(), Some(evm.to_string()));
+        assert_eq!(fixture.get_contenthash(SUB_DOMAIN_1).await.unwrap(), Some(uri.to_string()));
+
+        // the batch is atomic, an invalid update reverts the valid ones before it
+        let updates = RecordUpdates::new().address(None).text("", Some("value")).build();
+        assert!(fixture.multicall(SUB_DOMAIN_1, updates).await.unwrap_err().to_string().contains("EmptyKey"));
+        assert_eq!(fixture.resolve_domain(SUB_DOMAIN_1).await, Some(user_identity));
+        assert!(fixture.multicall(SUB_DOMAIN_1, vec![]).await.is_err());
+
+        // invalid addresses are rejected by the builder
+        assert!(RecordUpdates::new().addr(COIN_TYPE_BTC, Some(evm)).is_err());
+    }
+
+    #[test]
+    fn test_interface_ids() {
+        // must match the constants in shared
+        assert_eq!(
+            interface_id(TEXT_RESOLVER).0.to_vec(),
+            hex_to_bytes("b8733a88e6ff0aa92e9353cd83dd8c7becf7463605a323d7d68329ab6bed2cd6"),
+        );
+        assert_eq!(
+            interface_id(INTERFACE_SUPPORT).0.to_vec(),
+            hex_to_bytes("52b4607cb395a742657f98c4621a1a3c3d4f7eb26cd277b9bb4860a54f97ec0f"),
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resolver_supports_interface() {
+        let fixture = setup().await;
+        let resolver: ContractId = fixture.resolver_contract.id().clone().into();
+        for interface in [INTERFACE_SUPPORT, BASE_RESOLVER, TEXT_RESOLVER, ADDR_RESOLVER, CONTENTHASH_RESOLVER, MULTICALL_RESOLVER] {
+            assert!(fixture.supports_interface(resolver, interface).await.unwrap());
+        }
+        assert!(!fixture.supports_interface(resolver, "UnknownResolver").await.unwrap());
+
+        // contracts without the method support nothing
+        let registrar: ContractId = fixture.registrar_contract.id().clone().into();
+        assert!(!fixture.supports_interface(registrar, INTERFACE_SUPPORT).await.unwrap());
+        assert!(!fixture.supports_interface(registrar, TEXT_RESOLVER).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_records_of_resolver_without_support() {
+        let fixture = setup().await;
+        fixture.mint_domain(SUB_DOMAIN_PART_1, 1, COMMON_DEFAULT_FEE).await.unwrap();
+        fixture.set_text(SUB_DOMAIN_1, "url", Some("https://fuelname.com")).await.unwrap();
+        let registrar: ContractId = fixture.registrar_contract.id().clone().into();
+        fixture.set_trusted_resolver(registrar, true).await.unwrap();
+        fixture.set_domain_resolver_with_proof(&fixture.user, SUB_DOMAIN_1, SUB_DOMAIN_1, registrar).await.unwrap();
+
+        // reads return nothing and writes are refused instead of going to the default resolver
+        assert_eq!(fixture.get_text(SUB_DOMAIN_1, "url").await, None);
+        assert!(fixture.get_text_records(SUB_DOMAIN_1, &[]).await.is_empty());
+        let error = fixture.set_text(SUB_DOMAIN_1, "url", None).await.unwrap_err();
+        assert!(error.to_string().contains("doesn't support TextResolver"));
+        assert!(fixture.set_addr(SUB_DOMAIN_1, COIN_TYPE_ETH, None).await.is_err());
+        assert!(fixture.set_contenthash(SUB_DOMAIN_1, None).await.is_err());
+        assert!(fixture.multicall(SUB_DOMAIN_1, vec![]).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_set_resolver_validates_resolver() {
+        let fixture = setup().await;
+        fixture.mint_domain(SUB_DOMAIN_PART_1, 1, COMMON_DEFAULT_FEE).await.unwrap();
+        let resolver: ContractId = fixture.resolver_contract.id().clone().into();
+        let registrar: ContractId = fixture.registrar_contract.id().clone().into();
+        let random_resolver = ContractId::new(random());
+        assert!(fixture.is_trusted_resolver(resolver).await);
+
+        // neither a contract without the resolver interface nor a missing one is accepted
+        assert!(fixture.set_domain_resolver_with_proof(&fixture.user, SUB_DOMAIN_1, SUB_DOMAIN_1, registrar).await.is_err());
+        assert!(fixture.set_domain_resolver_with_proof(&fixture.user, SUB_DOMAIN_1, SUB_DOMAIN_1, random_resolver).await.is_err());
+
+        fixture.set_trusted_resolver(random_resolver, true).await.unwrap();
+        fixture.set_domain_resolver_with_proof(&fixture.user, SUB_DOMAIN_1, SUB_DOMAIN_1, random_resolver).await.unwrap();
+        assert_eq!(fixture.get_domain_resolver(SUB_DOMAIN_1).await, Some(random_resolver));
+    }
+
+    #[tokio::test]
+    async fn test_validated_resolver_is_called() {
+        let fixture = setup().await;
+        let user_identity = Identity::Address(fixture.user.address().into());
+        let asset = fixture.mint_domain(SUB_DOMAIN_PART_1, 1, COMMON_DEFAULT_FEE).await.unwrap();
+
+        // the resolver implementation behind the proxy passes the probe but isn't trusted
+        let resolver = fixture.contracts.get(&ContractType::Resolver).unwrap().target_id;
+        fixture.set_domain_resolver_with_proof(&fixture.user, SUB_DOMAIN_1, SUB_DOMAIN_1, resolver).await.unwrap();
+        assert!(!fixture.is_trusted_resolver(resolver).await);
+        Resolver::new(resolver, fixture.user.clone())
+            .methods()
+            .set(asset, Some(user_identity.clone()))
+            .with_contract_ids(&[
+                fixture.contracts.get(&ContractType::Registry).unwrap().proxy_id.into(),
+                fixture.contracts.get(&ContractType::Registry).unwrap().target_id.into(),
+            ])
+            .add_custom_asset(asset, 1, Some(fixture.user.address().into()))
+            .call()
+            .await
+            .unwrap();
+
+        fixture.set_primary(SUB_DOMAIN_1).await;
+        assert_eq!(fixture.reverse_resolve_domain(user_identity).await, Some(asset));
+    }
+
+    #[tokio::test]
+    async fn test_untrusted_resolver_is_not_called() {
+        let fixture = setup().await;
+        let user_identity = Identity::Address(fixture.user.address().into());
+        let resolver: ContractId = fixture.resolver_contract.id().clone().into();
+        let asset = fixture.mint_domain(SUB_DOMAIN_PART_1, 1, COMMON_DEFAULT_FEE).await.unwrap();
+        fixture.set_resolution(SUB_DOMAIN_1, Some(user_identity.clone())).await;
+        fixture.set_primary(SUB_DOMAIN_1).await;
+        assert_eq!(fixture.reverse_resolve_domain(user_identity.clone()).await, Some(asset));
+
+        // reverse resolution skips the resolver instead of reverting
+        fixture.set_trusted_resolver(resolver, false).await.unwrap();
+        assert_eq!(fixture.reverse_resolve_domain(user_identity.clone()).await, None);
+
+        // and setting it again is rejected although it passes the interface probe
+        let error = fixture.set_domain_resolver_with_proof(&fixture.user, SUB_DOMAIN_1, SUB_DOMAIN_1, resolver).await.unwrap_err();
+        assert!(error.to_string().contains("UnsupportedResolver"));
+        assert!(!fixture.is_trusted_resolver(resolver).await);
+
+        // only the registry owner trusts resolvers
+        let error = fixture.registry_contract
+            .clone()
+            .with_account(fixture.user.clone())
+            .methods()
+            .set_trusted_resolver(resolver, true)
+            .with_contract_ids(&[fixture.contracts.get(&ContractType::Registry).unwrap().target_id.into()])
+            .call()
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("NotOwner"));
+
+        fixture.set_trusted_resolver(resolver, true).await.unwrap();
+        assert_eq!(fixture.reverse_resolve_domain(user_identity).await, Some(asset));
+    }
 }
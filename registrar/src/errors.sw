@@ -14,3 +14,53 @@ pub enum GracePeriodError {
 pub enum DomainRenewalError {
     CanNotRenewRootDomain: (),
 }
+
+pub enum PremiumError {
+    InvalidDecayPeriod: (),
+}
+
+pub enum ReferralError {
+    InvalidReferralFee: (),
+    NothingToWithdraw: (),
+    SelfReferral: (),
+}
+
+pub enum VoucherError {
+    InvalidDiscount: (),
+    InvalidSignature: (),
+    SignerNotSet: (),
+    VoucherAlreadyUsed: (),
+    VoucherExpired: (),
+}
+
+pub enum TreasuryError {
+    InsufficientFunds: (),
+    InvalidBeneficiaryShares: (),
+    NothingToWithdraw: (),
+    TooManyBeneficiaries: (),
+}
+
+pub enum BatchError {
+    BatchTooLarge: (),
+    EmptyBatch: (),
+}
+
+pub enum PeriodError {
+    InvalidPeriodDuration: (),
+    InvalidPeriodLimits: (),
+    ZeroPeriodFee: (),
+}
+
+pub enum PausedError {
+    OperationPaused: (),
+}
+
+pub enum AccessControlError {
+    MissingRole: (),
+}
+
+pub enum RootDomainError {
+    InvalidRootDomain: (),
+    RootDomainAlreadySet: (),
+    RootDomainRegistered: (),
+}
@@ -5,10 +5,20 @@ mod errors;
 use ::errors::ValidationError;
 use ::errors::GracePeriodError;
 use ::errors::DomainRenewalError;
+use ::errors::PremiumError;
+use ::errors::ReferralError;
+use ::errors::VoucherError;
+use ::errors::TreasuryError;
+use ::errors::BatchError;
+use ::errors::PeriodError;
+use ::errors::PausedError;
+use ::errors::AccessControlError;
+use ::errors::RootDomainError;
 
-use shared::{DomainRegistrar, DomainRegistry};
-use std::{hash::Hash, constants::ZERO_B256, call_frames::{msg_asset_id}, string::String, outputs::{Output, output_type, output_count, output_amount, output_asset_id, output_asset_to}, block::timestamp, context::msg_amount, asset::transfer, context::this_balance};
+use shared::{AccessControl, Beneficiary, DiscountVoucher, DomainRegistrar, DomainRegistry, EmergencyStop, PausableOperation, PeriodLimits, Role, SimpleDomainResolver};
+use std::{hash::{Hash, sha256}, ecr::ec_recover_address, storage::{storage_vec::*, storage_string::*}, constants::ZERO_B256, primitive_conversions::u64::*, call_frames::{msg_asset_id}, string::String, outputs::{Output, output_type, output_count, output_amount, output_asset_id, output_asset_to}, block::timestamp, context::msg_amount, asset::transfer, context::this_balance};
 use sway_libs::ownership::*;
+use sway_libs::pausable::{_is_paused, _pause, _unpause};
 
 struct SetFeesEvent {
     asset_id: AssetId,
@@ -23,6 +33,76 @@ struct SetGracePeriodEvent {
     duration: u64,
 }
 
+struct SetExpiredPremiumEvent {
+    asset_id: AssetId,
+    start_premium: u64,
+}
+
+struct SetPremiumDecayPeriodEvent {
+    duration: u64,
+}
+
+struct SetReferralFeeEvent {
+    fee_bps: u64,
+}
+
+struct SetVoucherSignerEvent {
+    signer: Option<Address>,
+}
+
+struct ReferralFeeEvent {
+    referrer: Identity,
+    asset_id: AssetId,
+    amount: u64,
+}
+
+struct ReferralFeesWithdrawnEvent {
+    referrer: Identity,
+    asset_id: AssetId,
+    amount: u64,
+}
+
+struct VoucherUsedEvent {
+    id: b256,
+    discount_bps: u64,
+}
+
+struct PauseEvent {
+    operation: Option<PausableOperation>,
+}
+
+struct UnpauseEvent {
+    operation: Option<PausableOperation>,
+}
+
+struct RoleGrantedEvent {
+    role: Role,
+    account: Identity,
+}
+
+struct RoleRevokedEvent {
+    role: Role,
+    account: Identity,
+}
+
+struct SetRootDomainEvent {
+    root_domain: String,
+}
+
+struct SetPeriodLimitsEvent {
+    limits: PeriodLimits,
+}
+
+struct SetBeneficiariesEvent {
+    beneficiaries: Vec<Beneficiary>,
+}
+
+struct FundsWithdrawnEvent {
+    asset_id: AssetId,
+    recipient: Identity,
+    amount: u64,
+}
+
 struct Fees {
     three_letter_annual_fee: u64,
     four_letter_annual_fee: u64,
@@ -41,22 +121,129 @@ configurable {
 }
 
 const ONE_YEAR_SECONDS: u64 = 31622400; 
+const DEFAULT_MIN_PERIODS: u64 = 1;
+const DEFAULT_MAX_PERIODS: u64 = 3;
+const MIN_PERIOD_DURATION: u64 = 86400; // 1 day
 const MIN_GRACE_PERIOD_DURATION = 2592000; // 30 days
-const ROOT_DOMAIN: str[4] = __to_str_array("fuel");
+const DEFAULT_PREMIUM_DECAY_PERIOD: u64 = 1814400; // 21 days
+const MAX_BPS: u64 = 10000;
+const MAX_BENEFICIARIES: u64 = 10;
+const MAX_BATCH_SIZE: u64 = 10;
+// TLD used until `set_root_domain` is called, keeps the originally deployed registrar on `.fuel`
+const DEFAULT_ROOT_DOMAIN: str[4] = __to_str_array("fuel");
 
 storage {
     grace_period_duration: u64 = MIN_GRACE_PERIOD_DURATION,
     pricing: StorageMap<AssetId, Fees> = StorageMap {},
+    // premium charged right after a domain is released, decays linearly to zero over `premium_decay_period`
+    expired_premium: StorageMap<AssetId, u64> = StorageMap {},
+    premium_decay_period: u64 = DEFAULT_PREMIUM_DECAY_PERIOD,
+    referral_fee_bps: u64 = 0,
+    // fees accrued by referrers and not withdrawn yet, they are excluded from `withdraw_funds`
+    referral_balances: StorageMap<(Identity, AssetId), u64> = StorageMap {},
+    pending_referral_fees: StorageMap<AssetId, u64> = StorageMap {},
+    voucher_signer: Option<Address> = None,
+    used_vouchers: StorageMap<b256, bool> = StorageMap {},
+    // lifetime accounting per fee asset, the withdrawable amount is derived from the contract balance
+    collected_revenue: StorageMap<AssetId, u64> = StorageMap {},
+    withdrawn_funds: StorageMap<AssetId, u64> = StorageMap {},
+    beneficiaries: StorageVec<Beneficiary> = StorageVec {},
+    period_limits: PeriodLimits = PeriodLimits {
+        min_periods: DEFAULT_MIN_PERIODS,
+        max_periods: DEFAULT_MAX_PERIODS,
+        period_duration: ONE_YEAR_SECONDS,
+    },
+    paused_operations: StorageMap<PausableOperation, bool> = StorageMap {},
+    roles: StorageMap<(Role, Identity), bool> = StorageMap {},
+    root_domain: StorageString = StorageString {},
+}
+
+#[storage(read)]
+fn get_root_domain() -> String {
+    storage.root_domain.read_slice().unwrap_or(String::from_ascii_str(from_str_array(DEFAULT_ROOT_DOMAIN)))
+}
+
+#[storage(read)]
+fn has_role(role: Role, account: Identity) -> bool {
+    storage.roles.get((role, account)).try_read().unwrap_or(false)
+}
+
+#[storage(read)]
+fn only_role(role: Role) {
+    require(has_role(role, msg_sender().unwrap()), AccessControlError::MissingRole);
+}
+
+#[storage(write)]
+fn grant_all_roles(account: Identity) {
+    storage.roles.insert((Role::PricingAdmin, account), true);
+    storage.roles.insert((Role::Treasurer, account), true);
+    storage.roles.insert((Role::TldAdmin, account), true);
+    storage.roles.insert((Role::Pauser, account), true);
 }
 
 #[storage(read)]
-fn get_domain_price(asset: AssetId, domain: String, years: u64) -> u64 {
+fn is_paused(operation: PausableOperation) -> bool {
+    _is_paused() || storage.paused_operations.get(operation).try_read().unwrap_or(false)
+}
+
+#[storage(read)]
+fn require_not_paused(operation: PausableOperation) {
+    require(!is_paused(operation), PausedError::OperationPaused);
+}
+
+#[storage(read)]
+fn get_period_limits() -> PeriodLimits {
+    storage.period_limits.try_read().unwrap_or(PeriodLimits {
+        min_periods: DEFAULT_MIN_PERIODS,
+        max_periods: DEFAULT_MAX_PERIODS,
+        period_duration: ONE_YEAR_SECONDS,
+    })
+}
+
+fn bps_of(amount: u64, bps: u64) -> u64 {
+    u64::try_from(amount.as_u256() * bps.as_u256() / MAX_BPS.as_u256()).unwrap()
+}
+
+#[storage(read)]
+fn get_premium_decay_period() -> u64 {
+    storage.premium_decay_period.try_read().unwrap_or(DEFAULT_PREMIUM_DECAY_PERIOD)
+}
+
+/// Premium for re-minting a domain which recently passed its expiration + grace period.
+/// Starts at the configured amount when the grace period ends and decays linearly to zero.
+#[storage(read)]
+fn get_expired_domain_premium(asset: AssetId, domain: String) -> u64 {
+    let start_premium = storage.expired_premium.get(asset).try_read().unwrap_or(0);
+    let decay_period = get_premium_decay_period();
+    if start_premium == 0 || decay_period == 0 {
+        return 0;
+    }
+    let registry_contract = abi(DomainRegistry, REGISTRY_CONTRACT_ID.into());
+    let root_domain = get_root_domain();
+    let expiration = match registry_contract.get_expiration_by_parent(domain, root_domain) {
+        Some(exp) => exp,
+        None => return 0,
+    };
+    let grace_period = registry_contract.get_grace_period_by_parent(domain, root_domain).unwrap_or(0);
+    let released_at = expiration + grace_period;
+    let now = timestamp();
+    if now < released_at || now - released_at >= decay_period {
+        return 0;
+    }
+    let remaining = decay_period - (now - released_at);
+    let premium = start_premium.as_u256() * remaining.as_u256() / decay_period.as_u256();
+    u64::try_from(premium).unwrap()
+}
+
+#[storage(read)]
+fn get_domain_price(asset: AssetId, domain: String, periods: u64) -> u64 {
     if msg_sender().unwrap() == Identity::Address(Address::from(RESERVER_ADDRESS)) {
         return 0;
     }
     let length = domain.as_bytes().len();
     require(length >= 3, ValidationError::InvalidDomainName);
-    require(years > 0 && years <= 3, ValidationError::InvalidPeriod);
+    let limits = get_period_limits();
+    require(periods >= limits.min_periods && periods <= limits.max_periods, ValidationError::InvalidPeriod);
     let fees = storage.pricing.get(asset).try_read();
     require(fees.is_some(), ValidationError::WrongFeeAsset);
     let fees = fees.unwrap();
@@ -67,20 +254,139 @@ fn get_domain_price(asset: AssetId, domain: String, years: u64) -> u64 {
     } else {
         fees.long_domain_annual_fee
     };
-    annual_fee * years
+    // fees are annual, prorated to the configured period duration
+    let fee = u64::try_from(annual_fee.as_u256() * periods.as_u256() * limits.period_duration.as_u256() / ONE_YEAR_SECONDS.as_u256()).unwrap();
+    // a paid tier must not become free because the prorated fee rounds down
+    require(annual_fee == 0 || fee > 0, PeriodError::ZeroPeriodFee);
+    fee + get_expired_domain_premium(asset, domain)
 }
 
-#[storage(read)]
-fn check_domain_payment(name: String, years: u64) {
+#[storage(read, write)]
+fn check_payment(price: u64) {
     let asset_id = msg_asset_id();
-    let price = get_domain_price(asset_id, name, years);
     let paid = msg_amount();
-    require(price == paid, ValidationError::WrongFeeAmount);
+    require(paid >= price, ValidationError::WrongFeeAmount);
+    // return the surplus so that price changes between quote and submit don't revert the purchase
+    if paid > price {
+        transfer(msg_sender().unwrap(), asset_id, paid - price);
+    }
+    if price > 0 {
+        storage.collected_revenue.insert(asset_id, storage.collected_revenue.get(asset_id).try_read().unwrap_or(0) + price);
+    }
+}
+
+#[storage(read, write)]
+fn check_domain_payment(name: String, periods: u64) {
+    check_payment(get_domain_price(msg_asset_id(), name, periods));
 }
 
-fn years_from_now_ts(years: u64) -> u64 {
+/// Hash signed by the voucher signer, bound to this registrar (the proxy when called through it),
+/// to the buyer and to the domain so that a voucher seen in the mempool can't be used by someone else.
+fn voucher_hash(voucher: DiscountVoucher, buyer: Identity, domain: String) -> b256 {
+    sha256(((ContractId::this(), voucher.id, voucher.discount_bps, voucher.expires_at), buyer, domain))
+}
+
+#[storage(read)]
+fn validate_voucher(voucher: DiscountVoucher, domain: String) {
+    let signer = storage.voucher_signer.try_read().unwrap_or(None);
+    require(signer.is_some(), VoucherError::SignerNotSet);
+    require(voucher.discount_bps <= MAX_BPS, VoucherError::InvalidDiscount);
+    require(voucher.expires_at > timestamp(), VoucherError::VoucherExpired);
+    require(!storage.used_vouchers.get(voucher.id).try_read().unwrap_or(false), VoucherError::VoucherAlreadyUsed);
+    let recovered = ec_recover_address(voucher.signature, voucher_hash(voucher, msg_sender().unwrap(), domain));
+    require(recovered.is_ok() && recovered.unwrap() == signer.unwrap(), VoucherError::InvalidSignature);
+}
+
+fn apply_discount(price: u64, discount_bps: u64) -> u64 {
+    price - bps_of(price, discount_bps)
+}
+
+#[storage(read, write)]
+fn use_voucher(voucher: DiscountVoucher, domain: String, price: u64) -> u64 {
+    validate_voucher(voucher, domain);
+    storage.used_vouchers.insert(voucher.id, true);
+    log(VoucherUsedEvent {
+        id: voucher.id,
+        discount_bps: voucher.discount_bps,
+    });
+    apply_discount(price, voucher.discount_bps)
+}
+
+#[storage(read, write)]
+fn accrue_referral_fee(referrer: Identity, asset_id: AssetId, price: u64) {
+    let fee_bps = storage.referral_fee_bps.try_read().unwrap_or(0);
+    let amount = bps_of(price, fee_bps);
+    if amount == 0 {
+        return;
+    }
+    let key = (referrer, asset_id);
+    storage.referral_balances.insert(key, storage.referral_balances.get(key).try_read().unwrap_or(0) + amount);
+    storage.pending_referral_fees.insert(asset_id, storage.pending_referral_fees.get(asset_id).try_read().unwrap_or(0) + amount);
+    log(ReferralFeeEvent {
+        referrer,
+        asset_id,
+        amount,
+    });
+}
+
+/// Funds which can be withdrawn by the treasury: everything except the referral fees not claimed yet.
+/// Derived from the balance so that funds received before the accounting was introduced aren't locked.
+#[storage(read)]
+fn get_pending_funds(asset_id: AssetId) -> u64 {
+    let balance = this_balance(asset_id);
+    let referral_fees = storage.pending_referral_fees.get(asset_id).try_read().unwrap_or(0);
+    // the counters can disagree with the balance, e.g. after a direct transfer, report zero instead of reverting
+    if balance > referral_fees { balance - referral_fees } else { 0 }
+}
+
+#[storage(read, write)]
+fn send_funds(asset_id: AssetId, recipient: Identity, amount: u64) {
+    storage.withdrawn_funds.insert(asset_id, storage.withdrawn_funds.get(asset_id).try_read().unwrap_or(0) + amount);
+    transfer(recipient, asset_id, amount);
+    log(FundsWithdrawnEvent {
+        asset_id,
+        recipient,
+        amount,
+    });
+}
+
+#[storage(read)]
+fn register_domain(recipient: Identity, domain: String, periods: u64) -> AssetId {
+    let registry_contract = abi(DomainRegistry, REGISTRY_CONTRACT_ID.into());
+    let expiration_ts = periods_from_now_ts(periods);
+    registry_contract.register_sub_domain(
+        recipient,
+        get_root_domain(),
+        domain,
+        Some(expiration_ts),
+        // TODO: restrict max grace period in resolver. 
+        //  It probably can't exceed expiration ts + grace period of a root domain.
+        //  We can also enforce max grace period duration
+        Some(storage.grace_period_duration.read()),
+        DEFAULT_RESOLVER_CONTRACT_ID
+    )
+}
+
+/// Registers the domain for `recipient` and optionally sets its address in the default resolver.
+/// The resolver only accepts updates from the domain owner, so in that case the NFT is minted to
+/// the registrar first and forwarded to the recipient once the address is set on its behalf.
+#[storage(read)]
+fn register_domain_for(recipient: Identity, domain: String, periods: u64, resolve_to: Option<Identity>) -> AssetId {
+    let identity = match resolve_to {
+        Some(identity) => identity,
+        None => return register_domain(recipient, domain, periods),
+    };
+    let minted_asset = register_domain(Identity::ContractId(ContractId::this()), domain, periods);
+    let resolver_contract = abi(SimpleDomainResolver, DEFAULT_RESOLVER_CONTRACT_ID.into());
+    resolver_contract.set_for(minted_asset, Some(identity), recipient);
+    transfer(recipient, minted_asset, 1);
+    minted_asset
+}
+
+#[storage(read)]
+fn periods_from_now_ts(periods: u64) -> u64 {
     let block_tai_timestamp = timestamp();
-    let ttl = ONE_YEAR_SECONDS * years;
+    let ttl = get_period_limits().period_duration * periods;
     block_tai_timestamp + ttl
 }
 
@@ -89,9 +395,11 @@ impl DomainRegistrar for Contract {
     fn initialize() -> Identity {
         let sender = msg_sender().unwrap();
         initialize_ownership(sender);
+        grant_all_roles(sender);
         storage.pricing.insert(AssetId::base(), ETH_FEES);
         // set grace period here to make it accessible through proxy
         storage.grace_period_duration.write(MIN_GRACE_PERIOD_DURATION);
+        storage.premium_decay_period.write(DEFAULT_PREMIUM_DECAY_PERIOD);
         log(
             SetFeesEvent {
                 asset_id: AssetId::base(),
@@ -102,38 +410,85 @@ impl DomainRegistrar for Contract {
     }
 
     #[storage(read)]
-    fn domain_price(domain: String, years: u64, asset: AssetId) -> u64 {
-        get_domain_price(asset, domain, years)
+    fn domain_price(domain: String, periods: u64, asset: AssetId) -> u64 {
+        get_domain_price(asset, domain, periods)
     }
 
     #[payable]
-    #[storage(read)]
-    fn mint_domain(domain: String, years: u64) -> AssetId {
+    #[storage(read, write)]
+    fn mint_domain(domain: String, periods: u64) -> AssetId {
+        require_not_paused(PausableOperation::Mint);
         let sender = msg_sender().unwrap();
-        check_domain_payment(domain, years);
-        let registry_contract = abi(DomainRegistry, REGISTRY_CONTRACT_ID.into());
-        let expiration_ts = years_from_now_ts(years);
-        let minted_asset = registry_contract.register_sub_domain(
-            sender,
-            String::from_ascii_str(from_str_array(ROOT_DOMAIN)),
-            domain,
-            Some(expiration_ts),
-            // TODO: restrict max grace period in resolver. 
-            //  It probably can't exceed expiration ts + grace period of a root domain.
-            //  We can also enforce max grace period duration
-            Some(storage.grace_period_duration.read()),
-            DEFAULT_RESOLVER_CONTRACT_ID
-        );
-        minted_asset
+        check_domain_payment(domain, periods);
+        register_domain(sender, domain, periods)
+    }
+
+    #[payable]
+    #[storage(read, write)]
+    fn mint_domains(domains: Vec<(String, u64)>) -> Vec<AssetId> {
+        require_not_paused(PausableOperation::Mint);
+        require(domains.len() > 0, BatchError::EmptyBatch);
+        require(domains.len() <= MAX_BATCH_SIZE, BatchError::BatchTooLarge);
+        let sender = msg_sender().unwrap();
+        let asset_id = msg_asset_id();
+        let mut total_price = 0;
+        for purchase in domains.iter() {
+            let (domain, periods) = purchase;
+            total_price = total_price + get_domain_price(asset_id, domain, periods);
+        }
+        check_payment(total_price);
+        // any failed registration reverts the whole batch
+        let mut minted_assets = Vec::new();
+        for purchase in domains.iter() {
+            let (domain, periods) = purchase;
+            minted_assets.push(register_domain(sender, domain, periods));
+        }
+        minted_assets
     }
 
     #[payable]
+    #[storage(read, write)]
+    fn mint_domain_for(recipient: Identity, domain: String, periods: u64, resolve_to: Option<Identity>) -> AssetId {
+        require_not_paused(PausableOperation::Mint);
+        check_domain_payment(domain, periods);
+        register_domain_for(recipient, domain, periods, resolve_to)
+    }
+
+    #[payable]
+    #[storage(read, write)]
+    fn mint_domain_with_referrer(domain: String, periods: u64, referrer: Option<Identity>, voucher: Option<DiscountVoucher>) -> AssetId {
+        require_not_paused(PausableOperation::Mint);
+        let sender = msg_sender().unwrap();
+        let asset_id = msg_asset_id();
+        let price = match voucher {
+            Some(v) => use_voucher(v, domain, get_domain_price(asset_id, domain, periods)),
+            None => get_domain_price(asset_id, domain, periods),
+        };
+        check_payment(price);
+        match referrer {
+            Some(r) => {
+                require(r != sender, ReferralError::SelfReferral);
+                accrue_referral_fee(r, asset_id, price);
+            },
+            None => {},
+        }
+        register_domain(sender, domain, periods)
+    }
+
     #[storage(read)]
-    fn renew_domain(name: String, years: u64) {
+    fn domain_price_with_voucher(domain: String, periods: u64, asset: AssetId, voucher: DiscountVoucher) -> u64 {
+        validate_voucher(voucher, domain);
+        apply_discount(get_domain_price(asset, domain, periods), voucher.discount_bps)
+    }
+
+    #[payable]
+    #[storage(read, write)]
+    fn renew_domain(name: String, periods: u64) {
+        require_not_paused(PausableOperation::Renew);
         // TODO: forbid to keep ownership for more than 3 years
-        check_domain_payment(name, years);
+        check_domain_payment(name, periods);
         let registry_contract = abi(DomainRegistry, REGISTRY_CONTRACT_ID.into());
-        let current_expiration = match registry_contract.get_expiration_by_parent(name, String::from_ascii_str(from_str_array(ROOT_DOMAIN))) {
+        let current_expiration = match registry_contract.get_expiration_by_parent(name, get_root_domain()) {
             Some(exp) => exp,
             None => {
                         require(false, DomainRenewalError::CanNotRenewRootDomain);
@@ -142,14 +497,14 @@ impl DomainRegistrar for Contract {
         };
         registry_contract.renew_domain(
             name,
-            String::from_ascii_str(from_str_array(ROOT_DOMAIN)),
-            current_expiration + (years * ONE_YEAR_SECONDS)
+            get_root_domain(),
+            current_expiration + (periods * get_period_limits().period_duration)
         );
     }
 
     #[storage(read, write)]
     fn set_fees(asset: AssetId, three_letter_fee: u64, four_letter_fee: u64, long_domain_fee: u64) {
-        only_owner();
+        only_role(Role::PricingAdmin);
         let fees = Fees {
             three_letter_annual_fee: three_letter_fee,
             four_letter_annual_fee: four_letter_fee,
@@ -166,7 +521,7 @@ impl DomainRegistrar for Contract {
 
     #[storage(read, write)]
     fn set_grace_period(grace_period_duration: u64) {
-        only_owner();
+        only_role(Role::PricingAdmin);
         require(grace_period_duration >= MIN_GRACE_PERIOD_DURATION, GracePeriodError::InvalidGracePeriodDuration);
         storage.grace_period_duration.write(grace_period_duration);
         log(
@@ -176,22 +531,140 @@ impl DomainRegistrar for Contract {
         );
     }
 
+    #[storage(read, write)]
+    fn set_root_domain(root_domain: String) {
+        only_role(Role::TldAdmin);
+        require(storage.root_domain.read_slice().is_none(), RootDomainError::RootDomainAlreadySet);
+        // only before the registrar receives its high-level domain, so a live `.fuel` registrar can't be repointed
+        let registry_contract = abi(DomainRegistry, REGISTRY_CONTRACT_ID.into());
+        let current_root_asset = registry_contract.get_domain_asset_id(get_root_domain());
+        require(this_balance(current_root_asset) == 0, RootDomainError::RootDomainRegistered);
+        require(root_domain.as_bytes().len() > 0, RootDomainError::InvalidRootDomain);
+        storage.root_domain.write_slice(root_domain);
+        log(
+            SetRootDomainEvent {
+                root_domain
+            }
+        );
+    }
+
+    #[storage(read)]
+    fn get_root_domain() -> String {
+        get_root_domain()
+    }
+
     #[storage(read)]
     fn get_grace_period() -> u64 {
         storage.grace_period_duration.read()
     } 
 
+    #[storage(read, write)]
+    fn set_period_limits(min_periods: u64, max_periods: u64, period_duration: u64) {
+        only_role(Role::PricingAdmin);
+        require(min_periods > 0 && min_periods <= max_periods, PeriodError::InvalidPeriodLimits);
+        require(period_duration >= MIN_PERIOD_DURATION, PeriodError::InvalidPeriodDuration);
+        let limits = PeriodLimits {
+            min_periods,
+            max_periods,
+            period_duration,
+        };
+        storage.period_limits.write(limits);
+        log(
+            SetPeriodLimitsEvent {
+                limits
+            }
+        );
+    }
+
     #[storage(read)]
+    fn get_period_limits() -> PeriodLimits {
+        get_period_limits()
+    }
+
+    #[storage(read, write)]
     fn withdraw_funds(asset_id: AssetId) {
-        only_owner();
-        let sender = msg_sender().unwrap();
-        let balance = this_balance(asset_id);
-        transfer(sender, asset_id, balance);
+        only_role(Role::Treasurer);
+        let amount = get_pending_funds(asset_id);
+        require(amount > 0, TreasuryError::NothingToWithdraw);
+        let count = storage.beneficiaries.len();
+        if count == 0 {
+            send_funds(asset_id, msg_sender().unwrap(), amount);
+            return;
+        }
+        let mut distributed = 0;
+        let mut i = 0;
+        while i < count {
+            let beneficiary = storage.beneficiaries.get(i).unwrap().read();
+            // the last beneficiary receives the rounding remainder
+            let share = if i == count - 1 {
+                amount - distributed
+            } else {
+                bps_of(amount, beneficiary.share_bps)
+            };
+            distributed = distributed + share;
+            if share > 0 {
+                send_funds(asset_id, beneficiary.recipient, share);
+            }
+            i = i + 1;
+        }
+    }
+
+    #[storage(read, write)]
+    fn withdraw_funds_to(asset_id: AssetId, recipient: Identity, amount: u64) {
+        only_role(Role::Treasurer);
+        require(amount > 0 && amount <= get_pending_funds(asset_id), TreasuryError::InsufficientFunds);
+        send_funds(asset_id, recipient, amount);
+    }
+
+    #[storage(read, write)]
+    fn set_beneficiaries(beneficiaries: Vec<Beneficiary>) {
+        only_role(Role::Treasurer);
+        require(beneficiaries.len() <= MAX_BENEFICIARIES, TreasuryError::TooManyBeneficiaries);
+        let mut total_bps = 0;
+        for beneficiary in beneficiaries.iter() {
+            total_bps = total_bps + beneficiary.share_bps;
+        }
+        // an empty list sends withdrawals to the owner
+        require(beneficiaries.len() == 0 || total_bps == MAX_BPS, TreasuryError::InvalidBeneficiaryShares);
+        let _ = storage.beneficiaries.clear();
+        for beneficiary in beneficiaries.iter() {
+            storage.beneficiaries.push(beneficiary);
+        }
+        log(
+            SetBeneficiariesEvent {
+                beneficiaries
+            }
+        );
+    }
+
+    #[storage(read)]
+    fn get_beneficiaries() -> Vec<Beneficiary> {
+        storage.beneficiaries.load_vec()
+    }
+
+    #[storage(read)]
+    fn get_collected_revenue(asset_id: AssetId) -> u64 {
+        storage.collected_revenue.get(asset_id).try_read().unwrap_or(0)
+    }
+
+    #[storage(read)]
+    fn get_withdrawn_funds(asset_id: AssetId) -> u64 {
+        storage.withdrawn_funds.get(asset_id).try_read().unwrap_or(0)
+    }
+
+    #[storage(read)]
+    fn get_pending_funds(asset_id: AssetId) -> u64 {
+        get_pending_funds(asset_id)
+    }
+
+    #[storage(read)]
+    fn get_pending_referral_fees(asset_id: AssetId) -> u64 {
+        storage.pending_referral_fees.get(asset_id).try_read().unwrap_or(0)
     }
 
     #[storage(write)]
     fn remove_fee_asset(asset: AssetId) {
-        only_owner();
+        only_role(Role::PricingAdmin);
         let removed = storage.pricing.remove(asset);
         require(removed, ValidationError::WrongFeeAsset);
         log(
@@ -200,4 +673,156 @@ impl DomainRegistrar for Contract {
             }
         );
     }
+
+    #[storage(read, write)]
+    fn set_expired_premium(asset: AssetId, start_premium: u64) {
+        only_role(Role::PricingAdmin);
+        require(storage.pricing.get(asset).try_read().is_some(), ValidationError::WrongFeeAsset);
+        storage.expired_premium.insert(asset, start_premium);
+        log(
+            SetExpiredPremiumEvent {
+                asset_id: asset,
+                start_premium
+            }
+        );
+    }
+
+    #[storage(read)]
+    fn get_expired_premium(asset: AssetId) -> u64 {
+        storage.expired_premium.get(asset).try_read().unwrap_or(0)
+    }
+
+    #[storage(read, write)]
+    fn set_premium_decay_period(duration: u64) {
+        only_role(Role::PricingAdmin);
+        require(duration > 0, PremiumError::InvalidDecayPeriod);
+        storage.premium_decay_period.write(duration);
+        log(
+            SetPremiumDecayPeriodEvent {
+                duration
+            }
+        );
+    }
+
+    #[storage(read)]
+    fn get_premium_decay_period() -> u64 {
+        get_premium_decay_period()
+    }
+
+    #[storage(read)]
+    fn expired_domain_premium(domain: String, asset: AssetId) -> u64 {
+        get_expired_domain_premium(asset, domain)
+    }
+
+    #[storage(read, write)]
+    fn set_referral_fee(fee_bps: u64) {
+        only_role(Role::PricingAdmin);
+        require(fee_bps <= MAX_BPS, ReferralError::InvalidReferralFee);
+        storage.referral_fee_bps.write(fee_bps);
+        log(
+            SetReferralFeeEvent {
+                fee_bps
+            }
+        );
+    }
+
+    #[storage(read)]
+    fn get_referral_fee() -> u64 {
+        storage.referral_fee_bps.try_read().unwrap_or(0)
+    }
+
+    #[storage(read)]
+    fn get_referral_balance(referrer: Identity, asset_id: AssetId) -> u64 {
+        storage.referral_balances.get((referrer, asset_id)).try_read().unwrap_or(0)
+    }
+
+    #[storage(read, write)]
+    fn withdraw_referral_fees(asset_id: AssetId) {
+        let sender = msg_sender().unwrap();
+        let key = (sender, asset_id);
+        let amount = storage.referral_balances.get(key).try_read().unwrap_or(0);
+        require(amount > 0, ReferralError::NothingToWithdraw);
+        storage.referral_balances.insert(key, 0);
+        storage.pending_referral_fees.insert(asset_id, storage.pending_referral_fees.get(asset_id).read() - amount);
+        transfer(sender, asset_id, amount);
+        log(
+            ReferralFeesWithdrawnEvent {
+                referrer: sender,
+                asset_id,
+                amount
+            }
+        );
+    }
+
+    #[storage(read, write)]
+    fn set_voucher_signer(signer: Option<Address>) {
+        only_role(Role::PricingAdmin);
+        storage.voucher_signer.write(signer);
+        log(
+            SetVoucherSignerEvent {
+                signer
+            }
+        );
+    }
+
+    #[storage(read)]
+    fn get_voucher_signer() -> Option<Address> {
+        storage.voucher_signer.try_read().unwrap_or(None)
+    }
+
+    #[storage(read)]
+    fn is_voucher_used(id: b256) -> bool {
+        storage.used_vouchers.get(id).try_read().unwrap_or(false)
+    }
+}
+
+impl EmergencyStop for Contract {
+    #[storage(read, write)]
+    fn pause(operation: Option<PausableOperation>) {
+        only_role(Role::Pauser);
+        match operation {
+            Some(op) => storage.paused_operations.insert(op, true),
+            None => _pause(),
+        }
+        log(PauseEvent { operation });
+    }
+
+    #[storage(read, write)]
+    fn unpause(operation: Option<PausableOperation>) {
+        only_role(Role::Pauser);
+        match operation {
+            Some(op) => storage.paused_operations.insert(op, false),
+            None => _unpause(),
+        }
+        log(UnpauseEvent { operation });
+    }
+
+    #[storage(read)]
+    fn paused(operation: Option<PausableOperation>) -> bool {
+        match operation {
+            Some(op) => is_paused(op),
+            None => _is_paused(),
+        }
+    }
+}
+
+impl AccessControl for Contract {
+    #[storage(read, write)]
+    fn grant_role(role: Role, account: Identity) {
+        only_owner();
+        storage.roles.insert((role, account), true);
+        log(RoleGrantedEvent { role, account });
+    }
+
+    #[storage(read, write)]
+    fn revoke_role(role: Role, account: Identity) {
+        only_owner();
+        let _ = storage.roles.remove((role, account));
+        log(RoleRevokedEvent { role, account });
+    }
+
+    #[storage(read)]
+    fn has_role(role: Role, account: Identity) -> bool {
+        has_role(role, account)
+    }
 }
@@ -12,6 +12,8 @@ pub enum RenewalError {
     CanNotRenewRootDomain: (),
     InvalidExpirationValue: (),
     NoActiveDomainForRenewal: (),
+    RenewalLimitExceeded: (),
+    RenewalNotApproved: (),
     UnauthorizedTransactionSender: (),
 }
 
@@ -34,4 +36,17 @@ pub enum ResolutionError {
     CannotSetPrimaryForUnknownAddress: (),
     ExpiredDomain: (),
     ResolverIsNotSet: (),
-}
\ No newline at end of file
+    UnsupportedResolver: (),
+}
+
+pub enum PausedError {
+    OperationPaused: (),
+}
+
+pub enum AccessControlError {
+    MissingRole: (),
+}
+
+pub enum SubdomainError {
+    NotControlled: (),
+}
@@ -3,8 +3,8 @@ contract;
 mod string_util;
 mod errors;
 
-use ::errors::{AssetError, ValidationError, UnexpectedError, MintError, OwnershipError, RenewalError, ResolutionError};
-use shared::{BaseDomainResolver, DomainRegistry, is_asset_owner};
+use ::errors::{AssetError, ValidationError, UnexpectedError, MintError, OwnershipError, RenewalError, ResolutionError, PausedError, AccessControlError, SubdomainError};
+use shared::{AccessControl, BASE_RESOLVER_INTERFACE_ID, BaseDomainResolver, DomainRegistry, EmergencyStop, InterfaceSupport, OperatorResolver, PausableOperation, Role, SubdomainMode, SubdomainOptions, VersionedResolver, is_asset_owner};
 use standards::src20::{
     SetDecimalsEvent,
     SetNameEvent,
@@ -22,7 +22,7 @@ use sway_libs::asset::{
     },
     metadata::*,
 };
-use std::{hash::{Hash, sha256}, storage::storage_string::*, string::String, asset::mint_to, block::timestamp, inputs::{
+use std::{hash::{Hash, sha256}, storage::{storage_string::*, storage_vec::*}, string::String, asset::{mint_to, transfer}, block::timestamp, call_frames::msg_asset_id, context::msg_amount, inputs::{
         Input,
         input_asset_id,
         input_count,
@@ -30,23 +30,108 @@ use std::{hash::{Hash, sha256}, storage::storage_string::*, string::String, asse
         input_type,
     }};
 use sway_libs::ownership::*;
+use sway_libs::pausable::{_is_paused, _pause, _unpause};
 
 struct SetPrimaryEvent {
     sender: Identity,
     asset_id: AssetId,
 }
 
+struct RevokeSubdomainEvent {
+    asset_id: AssetId,
+    revoked_by: Identity,
+}
+
+struct SetRenewalLimitEvent {
+    asset_id: AssetId,
+    max_expiration: Option<u64>,
+}
+
+struct TransferDomainEvent {
+    asset_id: AssetId,
+    from: Identity,
+    to: Identity,
+    records_cleared: bool,
+}
+
+struct SetTrustedResolverEvent {
+    resolver: ContractId,
+    trusted: bool,
+}
+
+struct PauseEvent {
+    operation: Option<PausableOperation>,
+}
+
+struct UnpauseEvent {
+    operation: Option<PausableOperation>,
+}
+
+struct RoleGrantedEvent {
+    role: Role,
+    account: Identity,
+}
+
+struct RoleRevokedEvent {
+    role: Role,
+    account: Identity,
+}
+
 const EXPIRATION_KEY: str[10] = __to_str_array("expiration");
 const DOMAIN_NAME_KEY: str[11] = __to_str_array("domain_name");
 const URI_KEY: str[3] = __to_str_array("uri");
 const GRACE_PERIOD_KEY: str[12] = __to_str_array("grace_period");
 const RESOLVER_KEY: str[8] = __to_str_array("resolver");
+const PARENT_KEY: str[6] = __to_str_array("parent");
+const PARENT_ASSET_KEY: str[12] = __to_str_array("parent_asset");
+const SUBDOMAIN_MODE_KEY: str[14] = __to_str_array("subdomain_mode");
+const INHERIT_EXPIRATION_KEY: str[18] = __to_str_array("inherit_expiration");
+const MAX_SUBDOMAINS_PAGE_SIZE: u64 = 50;
+const EMANCIPATED_MODE: u64 = 0;
+const CONTROLLED_MODE: u64 = 1;
 
 storage {
     total_assets: u64 = 0,
     metadata: StorageMetadata = StorageMetadata {},
     asset_genesis: StorageMap<b256, u64> = StorageMap {},
     primary_domains: StorageMap<Identity, AssetId> = StorageMap {},
+    paused_operations: StorageMap<PausableOperation, bool> = StorageMap {},
+    roles: StorageMap<(Role, Identity), bool> = StorageMap {},
+    // latest expiration the holder of a subdomain generation may renew to without the parent
+    renewal_limits: StorageMap<AssetId, u64> = StorageMap {},
+    // every subdomain asset minted under a parent generation, including replaced and expired ones
+    sub_domains: StorageMap<AssetId, StorageVec<AssetId>> = StorageMap {},
+    // owner decisions about resolvers: trusted ones skip the interface probe and may report operators, untrusted ones
+    // are neither accepted nor called. Unlisted resolvers passed the probe, or were set before it existed, and are called
+    trusted_resolvers: StorageMap<ContractId, bool> = StorageMap {},
+}
+
+#[storage(read)]
+fn has_role(role: Role, account: Identity) -> bool {
+    storage.roles.get((role, account)).try_read().unwrap_or(false)
+}
+
+#[storage(read)]
+fn only_role(role: Role) {
+    require(has_role(role, msg_sender().unwrap()), AccessControlError::MissingRole);
+}
+
+#[storage(write)]
+fn grant_all_roles(account: Identity) {
+    storage.roles.insert((Role::PricingAdmin, account), true);
+    storage.roles.insert((Role::Treasurer, account), true);
+    storage.roles.insert((Role::TldAdmin, account), true);
+    storage.roles.insert((Role::Pauser, account), true);
+}
+
+#[storage(read)]
+fn is_paused(operation: PausableOperation) -> bool {
+    _is_paused() || storage.paused_operations.get(operation).try_read().unwrap_or(false)
+}
+
+#[storage(read)]
+fn require_not_paused(operation: PausableOperation) {
+    require(!is_paused(operation), PausedError::OperationPaused);
 }
 
 impl SRC20 for Contract {
@@ -117,8 +202,9 @@ fn get_domain_name(asset: AssetId) -> Option<String> {
     }
 }
 
+/// Whether the domain exists judging by its own expiration and grace period only
 #[storage(read)]
-fn asset_exists(asset: AssetId) -> bool {
+fn exists_by_own_expiration(asset: AssetId) -> bool {
     match get_domain_name(asset) {
         Some(_) => (),
         None => return false,
@@ -136,7 +222,7 @@ fn asset_exists(asset: AssetId) -> bool {
 }
 
 #[storage(read)]
-fn is_asset_active(asset: AssetId) -> bool {
+fn is_active_by_own_expiration(asset: AssetId) -> bool {
     let domain_was_registered = get_domain_name(asset).is_some();
     match storage.metadata.get(asset, String::from_ascii_str(from_str_array(EXPIRATION_KEY))) {
         Some(Metadata::Int(exp)) => exp > timestamp(),
@@ -144,6 +230,64 @@ fn is_asset_active(asset: AssetId) -> bool {
     }
 }
 
+/// Subdomains inheriting the expiration exist only while every ancestor they inherit from exists
+#[storage(read)]
+fn asset_exists(asset: AssetId) -> bool {
+    let mut current = asset;
+    while true {
+        if !exists_by_own_expiration(current) {
+            return false;
+        }
+        current = match get_expiration_parent(current) {
+            Some(parent) => parent,
+            None => return true,
+        };
+    }
+    true
+}
+
+#[storage(read)]
+fn is_asset_active(asset: AssetId) -> bool {
+    let mut current = asset;
+    while true {
+        if !is_active_by_own_expiration(current) {
+            return false;
+        }
+        current = match get_expiration_parent(current) {
+            Some(parent) => parent,
+            None => return true,
+        };
+    }
+    true
+}
+
+#[storage(read)]
+fn get_own_expiration(asset: AssetId) -> Option<u64> {
+    match storage.metadata.get(asset, String::from_ascii_str(from_str_array(EXPIRATION_KEY))) {
+        Some(Metadata::Int(exp)) => Some(exp),
+        _ => None,
+    }
+}
+
+/// The earliest expiration along the chain of ancestors the domain inherits its expiration from
+#[storage(read)]
+fn get_effective_expiration(asset: AssetId) -> Option<u64> {
+    let mut current = asset;
+    let mut effective: Option<u64> = None;
+    while true {
+        effective = match (effective, get_own_expiration(current)) {
+            (Some(exp), Some(own)) => if own < exp { Some(own) } else { Some(exp) },
+            (None, own) => own,
+            (exp, None) => exp,
+        };
+        current = match get_expiration_parent(current) {
+            Some(parent) => parent,
+            None => return effective,
+        };
+    }
+    effective
+}
+
 // TODO: https://docs.ens.domains/registry/eth#commit-reveal check if no attacks available on the mempool
 #[storage(read, write)]
 fn mint_token(recipient: Identity, full_name: String, expiration: Option<u64>, grace_period: Option<u64>, resolver: Option<ContractId>) -> AssetId {
@@ -183,6 +327,186 @@ fn set_token_metadata(asset: AssetId, full_name: String, expiration: Option<u64>
     }
 }
 
+/// Whether the asset is still the latest generation of its name and hasn't expired
+#[storage(read)]
+fn is_current_generation(asset: AssetId) -> bool {
+    match get_domain_name(asset) {
+        Some(name) => {
+            let (_, current_asset) = domain_to_asset_id(name);
+            current_asset == asset && asset_exists(asset)
+        },
+        None => false,
+    }
+}
+
+#[storage(read, write)]
+fn set_subdomain_metadata(asset: AssetId, parent: String, options: SubdomainOptions) {
+    let mode_value = match options.mode {
+        SubdomainMode::Emancipated => EMANCIPATED_MODE,
+        SubdomainMode::Controlled => CONTROLLED_MODE,
+    };
+    let (_, parent_asset) = domain_to_asset_id(parent);
+    storage.sub_domains.get(parent_asset).push(asset);
+    _set_metadata(storage.metadata, asset, String::from_ascii_str(from_str_array(PARENT_KEY)), Metadata::String(parent));
+    _set_metadata(storage.metadata, asset, String::from_ascii_str(from_str_array(PARENT_ASSET_KEY)), Metadata::B256(parent_asset.into()));
+    _set_metadata(storage.metadata, asset, String::from_ascii_str(from_str_array(SUBDOMAIN_MODE_KEY)), Metadata::Int(mode_value));
+    if options.inherit_expiration {
+        _set_metadata(storage.metadata, asset, String::from_ascii_str(from_str_array(INHERIT_EXPIRATION_KEY)), Metadata::Int(1));
+    }
+}
+
+#[storage(read)]
+fn get_subdomain_options(asset: AssetId) -> Option<SubdomainOptions> {
+    let mode = match storage.metadata.get(asset, String::from_ascii_str(from_str_array(SUBDOMAIN_MODE_KEY))) {
+        Some(Metadata::Int(mode)) => if mode == CONTROLLED_MODE {
+            SubdomainMode::Controlled
+        } else {
+            SubdomainMode::Emancipated
+        },
+        _ => return None,
+    };
+    Some(SubdomainOptions {
+        mode,
+        inherit_expiration: inherits_expiration(asset),
+    })
+}
+
+#[storage(read)]
+fn inherits_expiration(asset: AssetId) -> bool {
+    match storage.metadata.get(asset, String::from_ascii_str(from_str_array(INHERIT_EXPIRATION_KEY))) {
+        Some(Metadata::Int(flag)) => flag == 1,
+        _ => false,
+    }
+}
+
+/// The exact parent generation a subdomain inherits its expiration from.
+/// Unlike ownership checks, it doesn't follow re-mints of the parent name.
+#[storage(read)]
+fn get_expiration_parent(asset: AssetId) -> Option<AssetId> {
+    if !inherits_expiration(asset) {
+        return None;
+    }
+    match storage.metadata.get(asset, String::from_ascii_str(from_str_array(PARENT_ASSET_KEY))) {
+        Some(Metadata::B256(parent_asset)) => Some(AssetId::from(parent_asset)),
+        _ => None,
+    }
+}
+
+#[storage(read)]
+fn is_controlled(asset: AssetId) -> bool {
+    match get_subdomain_options(asset) {
+        Some(options) => match options.mode {
+            SubdomainMode::Controlled => true,
+            SubdomainMode::Emancipated => false,
+        },
+        None => false,
+    }
+}
+
+#[storage(read)]
+fn get_parent_asset(asset: AssetId) -> Option<AssetId> {
+    match storage.metadata.get(asset, String::from_ascii_str(from_str_array(PARENT_KEY))) {
+        Some(Metadata::String(parent)) => {
+            let (_, parent_asset) = domain_to_asset_id(parent);
+            Some(parent_asset)
+        },
+        _ => None,
+    }
+}
+
+/// The owner of a domain or, for controlled subdomains, the owner of the nearest ancestor
+/// reachable through controlled links.
+#[storage(read)]
+fn is_domain_controller(asset: AssetId) -> bool {
+    let mut current = asset;
+    while true {
+        if is_asset_owner(current) {
+            return true;
+        }
+        if !is_controlled(current) {
+            return false;
+        }
+        current = match get_parent_asset(current) {
+            Some(parent) => parent,
+            None => return false,
+        };
+    }
+    false
+}
+
+#[storage(read)]
+fn is_trusted_resolver(resolver: ContractId) -> bool {
+    storage.trusted_resolvers.get(resolver).try_read().unwrap_or(false)
+}
+
+/// Resolvers the owner untrusted, e.g. broken ones
+#[storage(read)]
+fn is_untrusted_resolver(resolver: ContractId) -> bool {
+    match storage.trusted_resolvers.get(resolver).try_read() {
+        Some(trusted) => !trusted,
+        None => false,
+    }
+}
+
+/// Probes resolvers the owner didn't trust for `BaseDomainResolver`. Contracts without `supports_interface` revert the
+/// probe and with it the transaction
+#[storage(read)]
+fn validate_resolver(resolver: ContractId) {
+    if is_trusted_resolver(resolver) {
+        return;
+    }
+    require(!is_untrusted_resolver(resolver), ResolutionError::UnsupportedResolver);
+    let resolver_contract = abi(InterfaceSupport, resolver.into());
+    require(resolver_contract.supports_interface(BASE_RESOLVER_INTERFACE_ID), ResolutionError::UnsupportedResolver);
+}
+
+#[storage(read, write)]
+fn mint_sub_domain(recipient: Identity, parent: String, name: String, expiration: Option<u64>, grace_period: Option<u64>, resolver: ContractId, options: SubdomainOptions) -> AssetId {
+    require_not_paused(PausableOperation::Mint);
+    let full_domain_name = check_parent_ownership_and_build_full_name(name, parent);
+    validate_domain_name_part(name);
+    validate_domain_name(full_domain_name);
+    let (_, parent_domain_asset) = domain_to_asset_id(parent);
+    let sub_domain_expiration = if options.inherit_expiration {
+        // the parent expiration applies dynamically, an own expiration only shortens it
+        require(expiration.is_some() || get_effective_expiration(parent_domain_asset).is_some(), ValidationError::ExpirationNotSet);
+        expiration
+    } else {
+        Some(get_expiration_for_subdomain(parent_domain_asset, expiration))
+    };
+    validate_resolver(resolver);
+    let minted_asset = mint_token(recipient, full_domain_name, sub_domain_expiration, grace_period, Some(resolver));
+    set_subdomain_metadata(minted_asset, parent, options);
+    minted_asset
+}
+
+/// Makes the current generation of a controlled subdomain inactive and returns its asset
+#[storage(read, write)]
+fn revoke_controlled_sub_domain(name: String, parent: String) -> AssetId {
+    let full_domain_name = check_parent_ownership_and_build_full_name(name, parent);
+    let (_, asset_id) = domain_to_asset_id(full_domain_name);
+    require(asset_exists(asset_id), AssetError::AssetDoesNotExist);
+    require(is_controlled(asset_id), SubdomainError::NotControlled);
+    _set_metadata(storage.metadata, asset_id, String::from_ascii_str(from_str_array(EXPIRATION_KEY)), Metadata::Int(timestamp()));
+    _set_metadata(storage.metadata, asset_id, String::from_ascii_str(from_str_array(GRACE_PERIOD_KEY)), Metadata::Int(0));
+    log(RevokeSubdomainEvent {
+        asset_id,
+        revoked_by: msg_sender().unwrap(),
+    });
+    asset_id
+}
+
+#[storage(read, write)]
+fn extend_expiration(asset_id: AssetId, expiration: u64) {
+    // check that asset exists and not exceed expiration + grace period
+    require(asset_exists(asset_id), RenewalError::NoActiveDomainForRenewal);
+    match get_own_expiration(asset_id) {
+        Some(exp) => require(expiration > exp, RenewalError::InvalidExpirationValue),
+        None => (),
+    };
+    _set_metadata(storage.metadata, asset_id, String::from_ascii_str(from_str_array(EXPIRATION_KEY)), Metadata::Int(expiration));
+}
+
 #[storage(read)]
 fn get_domain_gen(domain: String) -> u64 {
     let sha_domain = sha256(domain);
@@ -218,7 +542,7 @@ fn get_expiration_for_subdomain(asset: AssetId, expiration: Option<u64>) -> u64
 #[storage(read)]
 fn check_parent_ownership_and_build_full_name(name: String, parent: String) -> String {
     let (_, parent_domain_asset) = domain_to_asset_id(parent);
-    require(is_asset_owner(parent_domain_asset), OwnershipError::NotDomainOwner);
+    require(is_domain_controller(parent_domain_asset), OwnershipError::NotDomainOwner);
     string_util::build_domain_name(name, parent)
 }
 
@@ -233,10 +557,29 @@ fn get_resolved_address(asset: AssetId) -> Option<Identity> {
             return None;
         }
     };
+    // a call to a broken resolver would revert set_primary and reverse resolution, skip the ones the owner untrusted
+    if is_untrusted_resolver(resolver) {
+        return None;
+    }
     let resolver_contract = abi(BaseDomainResolver, resolver.into()); 
     resolver_contract.resolve(asset)
 } 
 
+/// Operators approved in the current resolver of the domain may replace it. Only trusted resolvers are asked, any
+/// other one could report every caller as an operator
+#[storage(read)]
+fn is_resolver_operator(asset: AssetId) -> bool {
+    let resolver = match get_resolver_for_asset(asset) {
+        Some(resolver) => resolver,
+        None => return false,
+    };
+    if !is_trusted_resolver(resolver) {
+        return false;
+    }
+    let resolver_contract = abi(OperatorResolver, resolver.into());
+    resolver_contract.is_operator(asset, msg_sender().unwrap())
+}
+
 #[storage(read)]
 fn get_resolver_for_asset(asset_id: AssetId) -> Option<ContractId> {
     if !asset_exists(asset_id) {
@@ -253,12 +596,13 @@ impl DomainRegistry for Contract {
     fn initialize() -> Identity {
         let sender = msg_sender().unwrap();
         initialize_ownership(sender);
+        grant_all_roles(sender);
         sender
     }
 
     #[storage(read, write)]
     fn register_high_level_domain(recipient: Identity, name: String) -> AssetId {
-        only_owner();
+        only_role(Role::TldAdmin);
         validate_domain_name_part(name);
         validate_domain_name(name);
         let minted_asset = mint_token(recipient, name, None, None, None);
@@ -267,26 +611,115 @@ impl DomainRegistry for Contract {
 
     #[storage(read, write)]
     fn register_sub_domain(recipient: Identity, parent: String, name: String, expiration: Option<u64>, grace_period: Option<u64>, resolver: ContractId) -> AssetId {
-        let full_domain_name = check_parent_ownership_and_build_full_name(name, parent);
-        validate_domain_name_part(name);
-        validate_domain_name(full_domain_name);
-        let (_, parent_domain_asset) = domain_to_asset_id(parent);
-        let sub_domain_expiration = get_expiration_for_subdomain(parent_domain_asset, expiration);
-        let minted_asset = mint_token(recipient, full_domain_name, Some(sub_domain_expiration), grace_period, Some(resolver));
+        let options = SubdomainOptions {
+            mode: SubdomainMode::Emancipated,
+            inherit_expiration: false,
+        };
+        mint_sub_domain(recipient, parent, name, expiration, grace_period, resolver, options)
+    }
+
+    #[storage(read, write)]
+    fn register_sub_domain_with_options(recipient: Identity, parent: String, name: String, expiration: Option<u64>, grace_period: Option<u64>, resolver: ContractId, options: SubdomainOptions) -> AssetId {
+        mint_sub_domain(recipient, parent, name, expiration, grace_period, resolver, options)
+    }
+
+    #[storage(read, write)]
+    fn revoke_sub_domain(name: String, parent: String) {
+        let _ = revoke_controlled_sub_domain(name, parent);
+    }
+
+    #[storage(read, write)]
+    fn reassign_sub_domain(name: String, parent: String, recipient: Identity) -> AssetId {
+        require_not_paused(PausableOperation::Mint);
+        let full_domain_name = string_util::build_domain_name(name, parent);
+        let (_, current_asset) = domain_to_asset_id(full_domain_name);
+        // read the terms before the revocation overwrites them
+        let expiration = get_own_expiration(current_asset);
+        let grace_period = match storage.metadata.get(current_asset, String::from_ascii_str(from_str_array(GRACE_PERIOD_KEY))) {
+            Some(Metadata::Int(gp)) => Some(gp),
+            _ => None,
+        };
+        let resolver = get_resolver_for_asset(current_asset);
+        // checks the parent ownership and the controlled mode, names without options aren't controlled
+        let _ = revoke_controlled_sub_domain(name, parent);
+        let options = get_subdomain_options(current_asset);
+        require(options.is_some(), SubdomainError::NotControlled);
+        let options = options.unwrap();
+        let minted_asset = mint_token(recipient, full_domain_name, expiration, grace_period, resolver);
+        set_subdomain_metadata(minted_asset, parent, options);
         minted_asset
     }
 
+    #[storage(read)]
+    fn get_subdomain_options(asset: AssetId) -> Option<SubdomainOptions> {
+        get_subdomain_options(asset)
+    }
+
+    #[storage(read)]
+    fn get_subdomains(parent: String, offset: u64, limit: u64) -> Vec<AssetId> {
+        let (_, parent_asset) = domain_to_asset_id(parent);
+        let index = storage.sub_domains.get(parent_asset);
+        let page_size = if limit > MAX_SUBDOMAINS_PAGE_SIZE { MAX_SUBDOMAINS_PAGE_SIZE } else { limit };
+        // compared without `offset + page_size`, which overflows for huge offsets
+        let end = if offset >= index.len() || page_size > index.len() - offset { index.len() } else { offset + page_size };
+        let mut sub_domains = Vec::new();
+        let mut i = offset;
+        while i < end {
+            let asset = index.get(i).unwrap().read();
+            if is_current_generation(asset) {
+                sub_domains.push(asset);
+            }
+            i = i + 1;
+        }
+        sub_domains
+    }
+
+    #[storage(read)]
+    fn get_subdomain_count(parent: String) -> u64 {
+        let (_, parent_asset) = domain_to_asset_id(parent);
+        storage.sub_domains.get(parent_asset).len()
+    }
+
     #[storage(read, write)]
     fn renew_domain(name: String, parent: String, expiration: u64) {
+        require_not_paused(PausableOperation::Renew);
         let full_domain_name = check_parent_ownership_and_build_full_name(name, parent);
         let (_, asset_id) = domain_to_asset_id(full_domain_name);
-        // check that asset exists and not exceed expiration + grace period
-        require(asset_exists(asset_id), RenewalError::NoActiveDomainForRenewal);
-        match storage.metadata.get(asset_id, String::from_ascii_str(from_str_array(EXPIRATION_KEY))) {
-            Some(Metadata::Int(exp)) => require(expiration > exp, RenewalError::InvalidExpirationValue),
-            _ => (),
-        };
-        _set_metadata(storage.metadata, asset_id, String::from_ascii_str(from_str_array(EXPIRATION_KEY)), Metadata::Int(expiration));
+        extend_expiration(asset_id, expiration);
+    }
+
+    #[storage(read, write)]
+    fn set_renewal_limit(name: String, parent: String, max_expiration: Option<u64>) {
+        let full_domain_name = check_parent_ownership_and_build_full_name(name, parent);
+        let (_, asset_id) = domain_to_asset_id(full_domain_name);
+        require(asset_exists(asset_id), AssetError::AssetDoesNotExist);
+        match max_expiration {
+            Some(limit) => storage.renewal_limits.insert(asset_id, limit),
+            None => {
+                let _ = storage.renewal_limits.remove(asset_id);
+            },
+        }
+        log(SetRenewalLimitEvent {
+            asset_id,
+            max_expiration,
+        });
+    }
+
+    #[storage(read)]
+    fn get_renewal_limit(domain: String) -> Option<u64> {
+        let (_, asset_id) = domain_to_asset_id(domain);
+        storage.renewal_limits.get(asset_id).try_read()
+    }
+
+    #[storage(read, write)]
+    fn self_renew_sub_domain(domain: String, expiration: u64) {
+        require_not_paused(PausableOperation::Renew);
+        let (_, asset_id) = domain_to_asset_id(domain);
+        require(is_asset_owner(asset_id), OwnershipError::NotDomainOwner);
+        let limit = storage.renewal_limits.get(asset_id).try_read();
+        require(limit.is_some(), RenewalError::RenewalNotApproved);
+        require(expiration <= limit.unwrap(), RenewalError::RenewalLimitExceeded);
+        extend_expiration(asset_id, expiration);
     }
 
     #[storage(read)]
@@ -312,16 +745,60 @@ impl DomainRegistry for Contract {
         domain_name_opt.unwrap()
     }
 
+    /// Asset id which the next mint of the domain will produce.
+    /// Lets clients reference a domain in the same transaction that mints it.
+    #[storage(read)]
+    fn get_next_domain_asset_id(domain: String) -> AssetId {
+        let sub_id = sha256(string_util::build_domain_hash_base(domain, get_domain_gen(domain) + 1));
+        AssetId::new(ContractId::this(), sub_id)
+    }
+
     #[storage(read, write)]
     fn set_resolver(domain: String, resolver: ContractId) {
+        require_not_paused(PausableOperation::SetResolver);
         let (_, asset) = domain_to_asset_id(domain); 
-        require(is_asset_owner(asset), OwnershipError::NotDomainOwner);
+        require(is_domain_controller(asset) || is_resolver_operator(asset), OwnershipError::NotDomainOwner);
         require(asset_exists(asset), AssetError::AssetDoesNotExist);
+        validate_resolver(resolver);
         _set_metadata(storage.metadata, asset, String::from_ascii_str(from_str_array(RESOLVER_KEY)), Metadata::B256(resolver.into()));
     }
 
+    #[storage(read, write)]
+    fn set_trusted_resolver(resolver: ContractId, trusted: bool) {
+        only_owner();
+        storage.trusted_resolvers.insert(resolver, trusted);
+        log(SetTrustedResolverEvent { resolver, trusted });
+    }
+
+    #[storage(read)]
+    fn is_trusted_resolver(resolver: ContractId) -> bool {
+        is_trusted_resolver(resolver)
+    }
+
+    #[payable]
+    #[storage(read)]
+    fn transfer_domain(recipient: Identity, clear_records: bool) {
+        let asset = msg_asset_id();
+        require(msg_amount() == 1 && asset_exists(asset), AssetError::AssetDoesNotExist);
+        if clear_records {
+            let resolver = get_resolver_for_asset(asset);
+            require(resolver.is_some(), ResolutionError::ResolverIsNotSet);
+            // the registry holds the NFT at this point, which proves ownership to the resolver
+            let resolver_contract = abi(VersionedResolver, resolver.unwrap().into());
+            resolver_contract.clear_records(asset);
+        }
+        transfer(recipient, asset, 1);
+        log(TransferDomainEvent {
+            asset_id: asset,
+            from: msg_sender().unwrap(),
+            to: recipient,
+            records_cleared: clear_records,
+        });
+    }
+
     #[storage(read, write)]
     fn set_primary(asset: AssetId) {
+        require_not_paused(PausableOperation::SetPrimary);
         let sender = msg_sender().unwrap(); 
         storage.primary_domains.insert(sender, asset);
         require(get_resolved_address(asset) == Some(sender), ResolutionError::CannotSetPrimaryForUnknownAddress);
@@ -355,24 +832,28 @@ impl DomainRegistry for Contract {
     #[storage(read)]
     fn get_expiration(domain: String) -> Option<u64> {
         let (_, asset) = domain_to_asset_id(domain);
-        match storage.metadata.get(asset, String::from_ascii_str(from_str_array(EXPIRATION_KEY))) {
-            Some(Metadata::Int(exp)) => Some(exp),
-            _ => None,
-        }
+        get_effective_expiration(asset)
     }
 
     #[storage(read)]
     fn get_expiration_by_parent(name: String, parent: String) -> Option<u64> {
         let domain = string_util::build_domain_name(name, parent);
         let (_, asset) = domain_to_asset_id(domain);
-        match storage.metadata.get(asset, String::from_ascii_str(from_str_array(EXPIRATION_KEY))) {
-            Some(Metadata::Int(exp)) => Some(exp),
+        get_effective_expiration(asset)
+    }
+
+    #[storage(read)]
+    fn get_grace_period(domain: String) -> Option<u64> {
+        let (_, asset) = domain_to_asset_id(domain);
+        match storage.metadata.get(asset, String::from_ascii_str(from_str_array(GRACE_PERIOD_KEY))) {
+            Some(Metadata::Int(grace_period)) => Some(grace_period),
             _ => None,
         }
     }
 
     #[storage(read)]
-    fn get_grace_period(domain: String) -> Option<u64> {
+    fn get_grace_period_by_parent(name: String, parent: String) -> Option<u64> {
+        let domain = string_util::build_domain_name(name, parent);
         let (_, asset) = domain_to_asset_id(domain);
         match storage.metadata.get(asset, String::from_ascii_str(from_str_array(GRACE_PERIOD_KEY))) {
             Some(Metadata::Int(grace_period)) => Some(grace_period),
@@ -382,6 +863,57 @@ impl DomainRegistry for Contract {
 
 }
 
+impl EmergencyStop for Contract {
+    #[storage(read, write)]
+    fn pause(operation: Option<PausableOperation>) {
+        only_role(Role::Pauser);
+        match operation {
+            Some(op) => storage.paused_operations.insert(op, true),
+            None => _pause(),
+        }
+        log(PauseEvent { operation });
+    }
+
+    #[storage(read, write)]
+    fn unpause(operation: Option<PausableOperation>) {
+        only_role(Role::Pauser);
+        match operation {
+            Some(op) => storage.paused_operations.insert(op, false),
+            None => _unpause(),
+        }
+        log(UnpauseEvent { operation });
+    }
+
+    #[storage(read)]
+    fn paused(operation: Option<PausableOperation>) -> bool {
+        match operation {
+            Some(op) => is_paused(op),
+            None => _is_paused(),
+        }
+    }
+}
+
+impl AccessControl for Contract {
+    #[storage(read, write)]
+    fn grant_role(role: Role, account: Identity) {
+        only_owner();
+        storage.roles.insert((role, account), true);
+        log(RoleGrantedEvent { role, account });
+    }
+
+    #[storage(read, write)]
+    fn revoke_role(role: Role, account: Identity) {
+        only_owner();
+        let _ = storage.roles.remove((role, account));
+        log(RoleRevokedEvent { role, account });
+    }
+
+    #[storage(read)]
+    fn has_role(role: Role, account: Identity) -> bool {
+        has_role(role, account)
+    }
+}
+
 // Tests
 #[test]
 fn test_total_assets() {
@@ -11,3 +11,10 @@ pub enum OwnershipError {
 pub enum ExpirationError {
     ExpiredDomain: (),
 }
+
+pub enum RecordError {
+    EmptyKey: (),
+    EmptyAddress: (),
+    EmptyContenthash: (),
+    NoUpdates: (),
+}
@@ -2,9 +2,32 @@ contract;
 
 mod errors;
 
-use ::errors::{ExpirationError, ResolveError, OwnershipError};
-use shared::{BaseDomainResolver, SimpleDomainResolver, is_asset_owner};
-use std::{constants::ZERO_B256, hash::Hash};
+use ::errors::{ExpirationError, RecordError, ResolveError, OwnershipError};
+use shared::{
+    ADDR_RESOLVER_INTERFACE_ID,
+    AddrResolver,
+    BASE_RESOLVER_INTERFACE_ID,
+    BaseDomainResolver,
+    CHECKED_RESOLVER_INTERFACE_ID,
+    CheckedDomainResolver,
+    CONTENTHASH_RESOLVER_INTERFACE_ID,
+    ContenthashResolver,
+    INTERFACE_SUPPORT_INTERFACE_ID,
+    InterfaceSupport,
+    is_asset_owner,
+    MULTICALL_RESOLVER_INTERFACE_ID,
+    MulticallResolver,
+    OPERATOR_RESOLVER_INTERFACE_ID,
+    OperatorResolver,
+    RecordUpdate,
+    SIMPLE_RESOLVER_INTERFACE_ID,
+    SimpleDomainResolver,
+    TEXT_RESOLVER_INTERFACE_ID,
+    TextResolver,
+    VERSIONED_RESOLVER_INTERFACE_ID,
+    VersionedResolver,
+};
+use std::{bytes::Bytes, constants::ZERO_B256, context::balance_of, hash::Hash, storage::{storage_bytes::*, storage_string::*}, string::String};
 use shared::DomainRegistry;
 
 struct SetAddressEvent {
@@ -12,12 +35,101 @@ struct SetAddressEvent {
     identity: Option<Identity>,
 }
 
+struct SetTextEvent {
+    asset: AssetId,
+    key: String,
+    value: Option<String>,
+}
+
+struct SetAddrEvent {
+    asset: AssetId,
+    coin_type: u64,
+    addr: Option<Bytes>,
+}
+
+struct SetContenthashEvent {
+    asset: AssetId,
+    hash: Option<Bytes>,
+}
+
+struct ClearRecordsEvent {
+    asset: AssetId,
+    version: u64,
+}
+
+/// `asset` is `None` for account-wide approvals
+struct OperatorSetEvent {
+    asset: Option<AssetId>,
+    owner: Identity,
+    operator: Identity,
+    approved: bool,
+}
+
 configurable {
     REGISTRY_CONTRACT_ID: ContractId = ContractId::from(ZERO_B256),
 }
 
 storage {
     resolved_addresses: StorageMap<AssetId, Identity> = StorageMap {},
+    // record version at the time the address was set, kept apart to stay compatible with addresses set before versioning
+    resolved_address_versions: StorageMap<AssetId, u64> = StorageMap {},
+    // bumped by `clear_records`, records of older versions are unreadable
+    record_versions: StorageMap<AssetId, u64> = StorageMap {},
+    texts: StorageMap<(AssetId, u64, String), StorageString> = StorageMap {},
+    // SLIP-44 coin type -> address in its binary form
+    addrs: StorageMap<(AssetId, u64, u64), StorageBytes> = StorageMap {},
+    // EIP-1577 encoded
+    contenthashes: StorageMap<(AssetId, u64), StorageBytes> = StorageMap {},
+    // last account which proved to hold the NFT in a record version, only its operators manage the records
+    record_owners: StorageMap<(AssetId, u64), Identity> = StorageMap {},
+    // approvals are bound to the owner who gave them, so they end with the first proof of the next owner
+    operators: StorageMap<(AssetId, u64, Identity, Identity), bool> = StorageMap {},
+    approvals_for_all: StorageMap<(Identity, Identity), bool> = StorageMap {},
+}
+
+#[storage(read)]
+fn get_record_version(asset: AssetId) -> u64 {
+    storage.record_versions.get(asset).try_read().unwrap_or(0)
+}
+
+#[storage(read)]
+fn is_record_operator(asset: AssetId, account: Identity) -> bool {
+    let version = get_record_version(asset);
+    let owner = match storage.record_owners.get((asset, version)).try_read() {
+        Some(owner) => owner,
+        None => return false,
+    };
+    // holdings of contracts can be checked, a contract which gave the NFT away loses its operators right away.
+    // Coins of addresses can't be, so their operators stay until the next holder proves ownership
+    match owner {
+        Identity::ContractId(contract_id) => {
+            if balance_of(contract_id, asset) == 0 {
+                return false;
+            }
+        },
+        _ => (),
+    };
+    storage.operators.get((asset, version, owner, account)).try_read().unwrap_or(false)
+        || storage.approvals_for_all.get((owner, account)).try_read().unwrap_or(false)
+}
+
+/// Requires the NFT and remembers the sender as the owner of the current record version
+#[storage(read, write)]
+fn only_record_owner(asset: AssetId) -> Identity {
+    require(is_asset_owner(asset), OwnershipError::NotDomainOwner);
+    let owner = msg_sender().unwrap();
+    storage.record_owners.insert((asset, get_record_version(asset)), owner);
+    owner
+}
+
+/// Either the holder of the NFT or one of its operators
+#[storage(read, write)]
+fn only_record_manager(asset: AssetId) {
+    if is_asset_owner(asset) {
+        let _ = only_record_owner(asset);
+        return;
+    }
+    require(is_record_operator(asset, msg_sender().unwrap()), OwnershipError::NotDomainOwner);
 }
 
 #[storage(read, write)]
@@ -25,6 +137,7 @@ fn set_resolved_address(asset: AssetId, resolve_to: Option<Identity>) {
     match resolve_to {
         Some(identity) => {
             storage.resolved_addresses.insert(asset, identity);
+            storage.resolved_address_versions.insert(asset, get_record_version(asset));
         },
         None => {
             let _ = storage.resolved_addresses.remove(asset);
@@ -32,6 +145,57 @@ fn set_resolved_address(asset: AssetId, resolve_to: Option<Identity>) {
     }
 }
 
+#[storage(read, write)]
+fn set_text_record(asset: AssetId, key: String, value: Option<String>) {
+    require(key.as_bytes().len() > 0, RecordError::EmptyKey);
+    let version = get_record_version(asset);
+    match value {
+        Some(text) => storage.texts.get((asset, version, key)).write_slice(text),
+        None => {
+            let _ = storage.texts.get((asset, version, key)).clear();
+        },
+    }
+    log(SetTextEvent { asset, key, value });
+}
+
+#[storage(read, write)]
+fn set_addr_record(asset: AssetId, coin_type: u64, addr: Option<Bytes>) {
+    let version = get_record_version(asset);
+    match addr {
+        Some(bytes) => {
+            require(bytes.len() > 0, RecordError::EmptyAddress);
+            storage.addrs.get((asset, version, coin_type)).write_slice(bytes);
+        },
+        None => {
+            let _ = storage.addrs.get((asset, version, coin_type)).clear();
+        },
+    }
+    log(SetAddrEvent { asset, coin_type, addr });
+}
+
+#[storage(read, write)]
+fn set_contenthash_record(asset: AssetId, hash: Option<Bytes>) {
+    let version = get_record_version(asset);
+    match hash {
+        Some(bytes) => {
+            require(bytes.len() > 0, RecordError::EmptyContenthash);
+            storage.contenthashes.get((asset, version)).write_slice(bytes);
+        },
+        None => {
+            let _ = storage.contenthashes.get((asset, version)).clear();
+        },
+    }
+    log(SetContenthashEvent { asset, hash });
+}
+
+#[storage(read)]
+fn get_resolved_address(asset: AssetId) -> Option<Identity> {
+    if storage.resolved_address_versions.get(asset).try_read().unwrap_or(0) != get_record_version(asset) {
+        return None;
+    }
+    storage.resolved_addresses.get(asset).try_read()
+}
+
 fn is_domain_active(asset: AssetId) -> bool {
     let registry_contract = abi(DomainRegistry, REGISTRY_CONTRACT_ID.into());
     registry_contract.is_domain_active(asset)
@@ -42,16 +206,155 @@ impl BaseDomainResolver for Contract {
     fn resolve(asset: AssetId) -> Option<Identity> {
         // Expired domains should be handled by the registry.
         // We can't enforce all resolvers to handle this
-        storage.resolved_addresses.get(asset).try_read()
+        get_resolved_address(asset)
+    }
+}
+
+impl CheckedDomainResolver for Contract {
+    #[storage(read)]
+    fn resolve_checked(asset: AssetId) -> Option<Identity> {
+        // expired, revoked and replaced generations are inactive in the registry
+        if !is_domain_active(asset) {
+            return None;
+        }
+        get_resolved_address(asset)
     }
 }
 
 impl SimpleDomainResolver for Contract {
     #[storage(read, write)]
     fn set(asset: AssetId, resolve_to: Option<Identity>) {
+        only_record_manager(asset);
+        set_resolved_address(asset, resolve_to);
+        require(is_domain_active(asset), ExpirationError::ExpiredDomain);
+        log(SetAddressEvent { asset, identity: resolve_to });
+    }
+
+    #[storage(read, write)]
+    fn set_for(asset: AssetId, resolve_to: Option<Identity>, owner: Identity) {
         require(is_asset_owner(asset), OwnershipError::NotDomainOwner);
+        storage.record_owners.insert((asset, get_record_version(asset)), owner);
         set_resolved_address(asset, resolve_to);
         require(is_domain_active(asset), ExpirationError::ExpiredDomain);
         log(SetAddressEvent { asset, identity: resolve_to });
     }
 }
+
+impl TextResolver for Contract {
+    #[storage(read, write)]
+    fn set_text(asset: AssetId, key: String, value: Option<String>) {
+        only_record_manager(asset);
+        set_text_record(asset, key, value);
+        require(is_domain_active(asset), ExpirationError::ExpiredDomain);
+    }
+
+    #[storage(read)]
+    fn text(asset: AssetId, key: String) -> Option<String> {
+        storage.texts.get((asset, get_record_version(asset), key)).read_slice()
+    }
+}
+
+impl AddrResolver for Contract {
+    #[storage(read, write)]
+    fn set_addr(asset: AssetId, coin_type: u64, addr: Option<Bytes>) {
+        only_record_manager(asset);
+        set_addr_record(asset, coin_type, addr);
+        require(is_domain_active(asset), ExpirationError::ExpiredDomain);
+    }
+
+    #[storage(read)]
+    fn addr(asset: AssetId, coin_type: u64) -> Option<Bytes> {
+        storage.addrs.get((asset, get_record_version(asset), coin_type)).read_slice()
+    }
+}
+
+impl ContenthashResolver for Contract {
+    #[storage(read, write)]
+    fn set_contenthash(asset: AssetId, hash: Option<Bytes>) {
+        only_record_manager(asset);
+        set_contenthash_record(asset, hash);
+        require(is_domain_active(asset), ExpirationError::ExpiredDomain);
+    }
+
+    #[storage(read)]
+    fn contenthash(asset: AssetId) -> Option<Bytes> {
+        storage.contenthashes.get((asset, get_record_version(asset))).read_slice()
+    }
+}
+
+impl MulticallResolver for Contract {
+    #[storage(read, write)]
+    fn multicall(asset: AssetId, updates: Vec<RecordUpdate>) {
+        only_record_manager(asset);
+        require(updates.len() > 0, RecordError::NoUpdates);
+        for update in updates.iter() {
+            match update {
+                RecordUpdate::Address(resolve_to) => {
+                    set_resolved_address(asset, resolve_to);
+                    log(SetAddressEvent { asset, identity: resolve_to });
+                },
+                RecordUpdate::Text((key, value)) => set_text_record(asset, key, value),
+                RecordUpdate::Addr((coin_type, addr)) => set_addr_record(asset, coin_type, addr),
+                RecordUpdate::Contenthash(hash) => set_contenthash_record(asset, hash),
+            }
+        }
+        require(is_domain_active(asset), ExpirationError::ExpiredDomain);
+    }
+}
+
+impl VersionedResolver for Contract {
+    #[storage(read, write)]
+    fn clear_records(asset: AssetId) {
+        // the registry passes this check while it holds the NFT during `transfer_domain`
+        require(is_asset_owner(asset), OwnershipError::NotDomainOwner);
+        let version = get_record_version(asset) + 1;
+        storage.record_versions.insert(asset, version);
+        log(ClearRecordsEvent { asset, version });
+    }
+
+    #[storage(read)]
+    fn record_version(asset: AssetId) -> u64 {
+        get_record_version(asset)
+    }
+}
+
+impl OperatorResolver for Contract {
+    #[storage(read, write)]
+    fn set_operator(asset: AssetId, operator: Identity, approved: bool) {
+        let owner = only_record_owner(asset);
+        storage.operators.insert((asset, get_record_version(asset), owner, operator), approved);
+        log(OperatorSetEvent { asset: Some(asset), owner, operator, approved });
+    }
+
+    #[storage(write)]
+    fn set_approval_for_all(operator: Identity, approved: bool) {
+        let owner = msg_sender().unwrap();
+        storage.approvals_for_all.insert((owner, operator), approved);
+        log(OperatorSetEvent { asset: None, owner, operator, approved });
+    }
+
+    #[storage(read)]
+    fn is_operator(asset: AssetId, account: Identity) -> bool {
+        is_record_operator(asset, account)
+    }
+
+    #[storage(read)]
+    fn is_approved_for_all(owner: Identity, operator: Identity) -> bool {
+        storage.approvals_for_all.get((owner, operator)).try_read().unwrap_or(false)
+    }
+}
+
+impl InterfaceSupport for Contract {
+    fn supports_interface(interface_id: b256) -> bool {
+        interface_id == INTERFACE_SUPPORT_INTERFACE_ID
+            || interface_id == BASE_RESOLVER_INTERFACE_ID
+            || interface_id == SIMPLE_RESOLVER_INTERFACE_ID
+            || interface_id == CHECKED_RESOLVER_INTERFACE_ID
+            || interface_id == TEXT_RESOLVER_INTERFACE_ID
+            || interface_id == ADDR_RESOLVER_INTERFACE_ID
+            || interface_id == CONTENTHASH_RESOLVER_INTERFACE_ID
+            || interface_id == VERSIONED_RESOLVER_INTERFACE_ID
+            || interface_id == OPERATOR_RESOLVER_INTERFACE_ID
+            || interface_id == MULTICALL_RESOLVER_INTERFACE_ID
+    }
+}
@@ -1,6 +1,113 @@
 library;
 
-use std::{constants::ZERO_B256, context::balance_of, string::String, inputs::{Input, input_asset_id, input_count, input_coin_owner, input_type}};
+use std::{b512::B512, bytes::Bytes, hash::{Hash, Hasher}, constants::ZERO_B256, context::balance_of, string::String, inputs::{Input, input_asset_id, input_count, input_coin_owner, input_type}};
+
+/// Discount signed off-chain by the registrar's voucher signer, can be used once
+pub struct DiscountVoucher {
+    pub id: b256,
+    pub discount_bps: u64,
+    pub expires_at: u64,
+    pub signature: B512,
+}
+
+/// Receives `share_bps` of every treasury withdrawal
+pub struct Beneficiary {
+    pub recipient: Identity,
+    pub share_bps: u64,
+}
+
+/// Allowed number of periods per purchase or renewal and the period length in seconds
+pub struct PeriodLimits {
+    pub min_periods: u64,
+    pub max_periods: u64,
+    pub period_duration: u64,
+}
+
+/// Chosen by the parent owner when minting a subdomain.
+/// Controlled subdomains can be revoked or re-assigned by the parent owner, who also passes their ownership checks.
+pub enum SubdomainMode {
+    Emancipated: (),
+    Controlled: (),
+}
+
+/// Chosen by the parent owner when minting a subdomain
+pub struct SubdomainOptions {
+    pub mode: SubdomainMode,
+    /// The subdomain stays active only while its parent is, so renewing the parent extends it.
+    /// An explicit expiration passed at mint can only shorten that.
+    pub inherit_expiration: bool,
+}
+
+/// Rules under which anyone can claim subdomains of a deposited parent domain.
+/// A zero `fee` makes claims free, `allowlist_only` restricts them to allowlisted accounts.
+pub struct SubdomainTerms {
+    pub fee_asset: AssetId,
+    pub fee: u64,
+    pub allowlist_only: bool,
+    pub options: SubdomainOptions,
+}
+
+/// Operations which can be paused individually in addition to the global pause
+pub enum PausableOperation {
+    Mint: (),
+    Renew: (),
+    SetResolver: (),
+    SetPrimary: (),
+}
+
+impl Hash for PausableOperation {
+    fn hash(self, ref mut state: Hasher) {
+        match self {
+            PausableOperation::Mint => 0u8.hash(state),
+            PausableOperation::Renew => 1u8.hash(state),
+            PausableOperation::SetResolver => 2u8.hash(state),
+            PausableOperation::SetPrimary => 3u8.hash(state),
+        }
+    }
+}
+
+/// Permissions which the owner can delegate instead of sharing the owner key
+pub enum Role {
+    PricingAdmin: (),
+    Treasurer: (),
+    TldAdmin: (),
+    Pauser: (),
+}
+
+impl Hash for Role {
+    fn hash(self, ref mut state: Hasher) {
+        match self {
+            Role::PricingAdmin => 0u8.hash(state),
+            Role::Treasurer => 1u8.hash(state),
+            Role::TldAdmin => 2u8.hash(state),
+            Role::Pauser => 3u8.hash(state),
+        }
+    }
+}
+
+abi AccessControl {
+    #[storage(read, write)]
+    fn grant_role(role: Role, account: Identity);
+
+    #[storage(read, write)]
+    fn revoke_role(role: Role, account: Identity);
+
+    #[storage(read)]
+    fn has_role(role: Role, account: Identity) -> bool;
+}
+
+/// Emergency stop switches, `None` refers to the global pause
+abi EmergencyStop {
+    #[storage(read, write)]
+    fn pause(operation: Option<PausableOperation>);
+
+    #[storage(read, write)]
+    fn unpause(operation: Option<PausableOperation>);
+
+    /// Whether the operation is paused either globally or individually, `None` returns the global state
+    #[storage(read)]
+    fn paused(operation: Option<PausableOperation>) -> bool;
+}
 
 abi DomainRegistry {
     #[storage(read, write)]
@@ -12,6 +119,29 @@ abi DomainRegistry {
     #[storage(read, write)]
     fn register_sub_domain(recipient: Identity, parent: String, name: String, expiration: Option<u64>, grace_period: Option<u64>, resolver: ContractId) -> AssetId;
 
+    #[storage(read, write)]
+    fn register_sub_domain_with_options(recipient: Identity, parent: String, name: String, expiration: Option<u64>, grace_period: Option<u64>, resolver: ContractId, options: SubdomainOptions) -> AssetId;
+
+    /// Ends the current generation of a controlled subdomain, so it can be minted again
+    #[storage(read, write)]
+    fn revoke_sub_domain(name: String, parent: String);
+
+    /// Revokes a controlled subdomain and mints it to `recipient` with the same expiration, resolver and options
+    #[storage(read, write)]
+    fn reassign_sub_domain(name: String, parent: String, recipient: Identity) -> AssetId;
+
+    #[storage(read)]
+    fn get_subdomain_options(asset: AssetId) -> Option<SubdomainOptions>;
+
+    /// Active subdomains of the current generation of `parent`.
+    /// Paginates over the index of all minted subdomain generations, so a page may hold fewer than `limit` assets.
+    #[storage(read)]
+    fn get_subdomains(parent: String, offset: u64, limit: u64) -> Vec<AssetId>;
+
+    /// Size of the subdomain index of `parent`, including expired and replaced generations
+    #[storage(read)]
+    fn get_subdomain_count(parent: String) -> u64;
+
     #[storage(read)]
     fn domain_exists(asset: AssetId) -> bool;
 
@@ -23,13 +153,26 @@ abi DomainRegistry {
 
     #[storage(read)]
     fn get_domain_name(asset: AssetId) -> String;
+
+    #[storage(read)]
+    fn get_next_domain_asset_id(domain: String) -> AssetId;
     
+    /// The resolver must be trusted or pass the `BaseDomainResolver` interface probe, untrusted ones are rejected
     #[storage(read, write)]
     fn set_resolver(domain: String, resolver: ContractId);
 
+    /// Owner override of the probe: trusted resolvers skip it and may report operators, untrusted ones are rejected by
+    /// `set_resolver` and no longer called on resolution, e.g. when they are broken
+    #[storage(read, write)]
+    fn set_trusted_resolver(resolver: ContractId, trusted: bool);
+
+    #[storage(read)]
+    fn is_trusted_resolver(resolver: ContractId) -> bool;
+
     #[storage(read)]
     fn get_resolver(domain: String) -> Option<ContractId>;
 
+    /// Takes the expiration of ancestors into account for subdomains which inherit it
     #[storage(read)]
     fn get_expiration(domain: String) -> Option<u64>;
 
@@ -39,9 +182,29 @@ abi DomainRegistry {
     #[storage(read)]
     fn get_grace_period(domain: String) -> Option<u64>;
 
+    #[storage(read)]
+    fn get_grace_period_by_parent(name: String, parent: String) -> Option<u64>;
+
     #[storage(read, write)]
     fn renew_domain(name: String, parent: String, expiration: u64);
 
+    /// Lets the holder of `name.parent` renew it up to `max_expiration`, `None` withdraws the approval
+    #[storage(read, write)]
+    fn set_renewal_limit(name: String, parent: String, max_expiration: Option<u64>);
+
+    #[storage(read)]
+    fn get_renewal_limit(domain: String) -> Option<u64>;
+
+    /// Renewal by the subdomain holder within the limit approved by the parent
+    #[storage(read, write)]
+    fn self_renew_sub_domain(domain: String, expiration: u64);
+
+    /// Forwards the domain NFT sent with the call to `recipient`. With `clear_records` the records of the previous
+    /// owner are wiped first, which requires the domain resolver to implement `VersionedResolver`
+    #[payable]
+    #[storage(read)]
+    fn transfer_domain(recipient: Identity, clear_records: bool);
+
     #[storage(read, write)]
     fn set_primary(asset: AssetId);
 
@@ -56,9 +219,114 @@ abi BaseDomainResolver {
     fn resolve(asset: AssetId) -> Option<Identity>;
 }
 
+// Interface ids of the resolver ABIs, `sha256` of the ABI name
+pub const INTERFACE_SUPPORT_INTERFACE_ID: b256 = 0x52b4607cb395a742657f98c4621a1a3c3d4f7eb26cd277b9bb4860a54f97ec0f;
+pub const BASE_RESOLVER_INTERFACE_ID: b256 = 0x3647268b455b1706aef84d42b6650a09630c2ee71f024138d31108e392627529;
+pub const SIMPLE_RESOLVER_INTERFACE_ID: b256 = 0x7d222f647ac13ba3a035f1255f6524ba8140a7d39527d69568741885a81546f3;
+pub const CHECKED_RESOLVER_INTERFACE_ID: b256 = 0x62ca1b86bb3aef93b1c7fab3fbba36f1ffe647ef0c86df74dd7d59891be6c622;
+pub const TEXT_RESOLVER_INTERFACE_ID: b256 = 0xb8733a88e6ff0aa92e9353cd83dd8c7becf7463605a323d7d68329ab6bed2cd6;
+pub const ADDR_RESOLVER_INTERFACE_ID: b256 = 0x46d1adb69ab27ed6668e913be0a38b2c1a51d456f41e3f1015bf148d499398b8;
+pub const CONTENTHASH_RESOLVER_INTERFACE_ID: b256 = 0xf774823cae41688c433ca7d072da4e49c709af37b77ef308bdccf093b85bf8b1;
+pub const VERSIONED_RESOLVER_INTERFACE_ID: b256 = 0x27ce594bbd27fa21782eb3b2bd61390235e78c83bb17824a5a1b45228d1dea96;
+pub const OPERATOR_RESOLVER_INTERFACE_ID: b256 = 0x0c9d5b3fc573e5eacdb2bce6f3a4faa721b241a90c6b927637177871b8057207;
+pub const MULTICALL_RESOLVER_INTERFACE_ID: b256 = 0x4a2a93a3533e683bce6c11813d90d0c4db8c5809e7458109459aca01422f7999;
+
+/// Lets clients find out which record types a resolver supports before calling it, similar to ERC-165
+abi InterfaceSupport {
+    fn supports_interface(interface_id: b256) -> bool;
+}
+
+/// For integrators calling the resolver directly, unlike `BaseDomainResolver::resolve` it returns `None` for domains
+/// which are not active in the registry
+abi CheckedDomainResolver {
+    #[storage(read)]
+    fn resolve_checked(asset: AssetId) -> Option<Identity>;
+}
+
 abi SimpleDomainResolver {
     #[storage(read, write)]
     fn set(asset: AssetId, resolve_to: Option<Identity>);
+
+    /// Sets the address as the holder of the NFT and records `owner` instead of the sender as the owner of the
+    /// records, for contracts which hold the NFT only to forward it to `owner`
+    #[storage(read, write)]
+    fn set_for(asset: AssetId, resolve_to: Option<Identity>, owner: Identity);
+}
+
+/// ENS-style key/value records, e.g. `avatar`, `url`, `email`, `com.twitter`, `com.github` or `description`
+abi TextResolver {
+    /// `None` clears the record
+    #[storage(read, write)]
+    fn set_text(asset: AssetId, key: String, value: Option<String>);
+
+    #[storage(read)]
+    fn text(asset: AssetId, key: String) -> Option<String>;
+}
+
+/// Addresses on other chains keyed by their SLIP-44 coin type (`0` Bitcoin, `60` Ethereum, `501` Solana),
+/// stored in the binary form used by ENS
+abi AddrResolver {
+    /// `None` clears the record
+    #[storage(read, write)]
+    fn set_addr(asset: AssetId, coin_type: u64, addr: Option<Bytes>);
+
+    #[storage(read)]
+    fn addr(asset: AssetId, coin_type: u64) -> Option<Bytes>;
+}
+
+/// Content hash as defined by EIP-1577, e.g. an IPFS or Arweave hosted frontend of the domain
+abi ContenthashResolver {
+    /// `None` clears the record
+    #[storage(read, write)]
+    fn set_contenthash(asset: AssetId, hash: Option<Bytes>);
+
+    #[storage(read)]
+    fn contenthash(asset: AssetId) -> Option<Bytes>;
+}
+
+/// Single record change applied by `MulticallResolver::multicall`, `None` clears the record
+pub enum RecordUpdate {
+    Address: Option<Identity>,
+    /// key, value
+    Text: (String, Option<String>),
+    /// SLIP-44 coin type, address
+    Addr: (u64, Option<Bytes>),
+    Contenthash: Option<Bytes>,
+}
+
+abi MulticallResolver {
+    /// Applies all updates in one transaction, any invalid update reverts the whole batch
+    #[storage(read, write)]
+    fn multicall(asset: AssetId, updates: Vec<RecordUpdate>);
+}
+
+/// Operators edit records without the domain NFT. The registry also lets them change the resolver of the domain if
+/// it trusts the resolver
+abi OperatorResolver {
+    /// Operator of a single domain, bound to the sender and the current record version so `clear_records` or the
+    /// first proof of the next owner revokes it
+    #[storage(read, write)]
+    fn set_operator(asset: AssetId, operator: Identity, approved: bool);
+
+    /// Operator of every domain the sender has proven to hold with the NFT in its current record version
+    #[storage(write)]
+    fn set_approval_for_all(operator: Identity, approved: bool);
+
+    #[storage(read)]
+    fn is_operator(asset: AssetId, account: Identity) -> bool;
+
+    #[storage(read)]
+    fn is_approved_for_all(owner: Identity, operator: Identity) -> bool;
+}
+
+/// Records are stored per version of the asset, so all of them can be wiped at once, e.g. for a new owner
+abi VersionedResolver {
+    /// Bumps the record version, records set before become unreadable
+    #[storage(read, write)]
+    fn clear_records(asset: AssetId);
+
+    #[storage(read)]
+    fn record_version(asset: AssetId) -> u64;
 }
 
 abi DomainRegistrar {
@@ -66,15 +334,30 @@ abi DomainRegistrar {
     fn initialize() -> Identity;
 
     #[storage(read)]
-    fn domain_price(domain: String, years: u64, asset: AssetId) -> u64;
+    fn domain_price(domain: String, periods: u64, asset: AssetId) -> u64;
 
     #[payable]
-    #[storage(read)]
-    fn mint_domain(domain: String, years: u64) -> AssetId;
+    #[storage(read, write)]
+    fn mint_domain(domain: String, periods: u64) -> AssetId;
+
+    #[payable]
+    #[storage(read, write)]
+    fn mint_domains(domains: Vec<(String, u64)>) -> Vec<AssetId>;
+
+    #[payable]
+    #[storage(read, write)]
+    fn mint_domain_for(recipient: Identity, domain: String, periods: u64, resolve_to: Option<Identity>) -> AssetId;
 
     #[payable]
+    #[storage(read, write)]
+    fn mint_domain_with_referrer(domain: String, periods: u64, referrer: Option<Identity>, voucher: Option<DiscountVoucher>) -> AssetId;
+
     #[storage(read)]
-    fn renew_domain(domain: String, years: u64);
+    fn domain_price_with_voucher(domain: String, periods: u64, asset: AssetId, voucher: DiscountVoucher) -> u64;
+
+    #[payable]
+    #[storage(read, write)]
+    fn renew_domain(domain: String, periods: u64);
 
     #[storage(read, write)]
     fn set_fees(asset: AssetId, three_letter_fee: u64, four_letter_fee: u64, long_domain_fee: u64);
@@ -85,11 +368,115 @@ abi DomainRegistrar {
     #[storage(read)]
     fn get_grace_period() -> u64;
 
+    #[storage(read, write)]
+    fn set_root_domain(root_domain: String);
+
+    #[storage(read)]
+    fn get_root_domain() -> String;
+
+    #[storage(read, write)]
+    fn set_period_limits(min_periods: u64, max_periods: u64, period_duration: u64);
+
     #[storage(read)]
+    fn get_period_limits() -> PeriodLimits;
+
+    #[storage(read, write)]
     fn withdraw_funds(asset_id: AssetId);
 
+    #[storage(read, write)]
+    fn withdraw_funds_to(asset_id: AssetId, recipient: Identity, amount: u64);
+
+    #[storage(read, write)]
+    fn set_beneficiaries(beneficiaries: Vec<Beneficiary>);
+
+    #[storage(read)]
+    fn get_beneficiaries() -> Vec<Beneficiary>;
+
+    #[storage(read)]
+    fn get_collected_revenue(asset_id: AssetId) -> u64;
+
+    #[storage(read)]
+    fn get_withdrawn_funds(asset_id: AssetId) -> u64;
+
+    #[storage(read)]
+    fn get_pending_funds(asset_id: AssetId) -> u64;
+
+    #[storage(read)]
+    fn get_pending_referral_fees(asset_id: AssetId) -> u64;
+
     #[storage(write)]
     fn remove_fee_asset(asset: AssetId);
+
+    #[storage(read, write)]
+    fn set_expired_premium(asset: AssetId, start_premium: u64);
+
+    /// Premium charged in `asset` right after a domain is released
+    #[storage(read)]
+    fn get_expired_premium(asset: AssetId) -> u64;
+
+    #[storage(read, write)]
+    fn set_premium_decay_period(duration: u64);
+
+    #[storage(read)]
+    fn get_premium_decay_period() -> u64;
+
+    #[storage(read)]
+    fn expired_domain_premium(domain: String, asset: AssetId) -> u64;
+
+    #[storage(read, write)]
+    fn set_referral_fee(fee_bps: u64);
+
+    #[storage(read)]
+    fn get_referral_fee() -> u64;
+
+    #[storage(read)]
+    fn get_referral_balance(referrer: Identity, asset_id: AssetId) -> u64;
+
+    #[storage(read, write)]
+    fn withdraw_referral_fees(asset_id: AssetId);
+
+    #[storage(read, write)]
+    fn set_voucher_signer(signer: Option<Address>);
+
+    #[storage(read)]
+    fn get_voucher_signer() -> Option<Address>;
+
+    #[storage(read)]
+    fn is_voucher_used(id: b256) -> bool;
+}
+
+/// Lets a domain owner hand out subdomains without presenting the parent NFT for every mint.
+/// The parent NFT is kept by the contract until the depositor withdraws it.
+abi SubdomainRegistrar {
+    #[payable]
+    #[storage(read, write)]
+    fn deposit_domain(domain: String);
+
+    #[storage(read, write)]
+    fn withdraw_domain(parent: AssetId);
+
+    #[storage(read, write)]
+    fn set_subdomain_terms(parent: AssetId, terms: Option<SubdomainTerms>);
+
+    #[storage(read, write)]
+    fn set_allowlisted(parent: AssetId, account: Identity, allowed: bool);
+
+    /// Revokes a controlled subdomain on behalf of the parent owner while the parent is deposited
+    #[storage(read)]
+    fn revoke_subdomain(parent_domain: String, name: String);
+
+    #[payable]
+    #[storage(read, write)]
+    fn claim_subdomain(parent_domain: String, name: String) -> AssetId;
+
+    #[storage(read)]
+    fn get_parent_owner(parent: AssetId) -> Option<Identity>;
+
+    #[storage(read)]
+    fn get_subdomain_terms(parent: AssetId) -> Option<SubdomainTerms>;
+
+    #[storage(read)]
+    fn is_allowlisted(parent: AssetId, account: Identity) -> bool;
 }
 
 pub fn is_asset_owner(asset_id: AssetId) -> bool {
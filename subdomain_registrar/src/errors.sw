@@ -0,0 +1,18 @@
+library;
+
+pub enum DepositError {
+    DomainNotActive: (),
+    WrongDomainAsset: (),
+}
+
+pub enum OwnershipError {
+    NotParentOwner: (),
+}
+
+pub enum ClaimError {
+    ClaimsClosed: (),
+    NotAllowlisted: (),
+    ParentNotDeposited: (),
+    WrongFeeAmount: (),
+    WrongFeeAsset: (),
+}
@@ -0,0 +1,176 @@
+contract;
+
+mod errors;
+
+use ::errors::{ClaimError, DepositError, OwnershipError};
+use shared::{DomainRegistry, SubdomainRegistrar, SubdomainTerms};
+use std::{asset::transfer, call_frames::msg_asset_id, constants::ZERO_B256, context::msg_amount, hash::Hash, string::String};
+
+struct DomainDepositedEvent {
+    parent: AssetId,
+    owner: Identity,
+}
+
+struct DomainWithdrawnEvent {
+    parent: AssetId,
+    owner: Identity,
+}
+
+struct SetSubdomainTermsEvent {
+    parent: AssetId,
+    terms: Option<SubdomainTerms>,
+}
+
+struct SetAllowlistedEvent {
+    parent: AssetId,
+    account: Identity,
+    allowed: bool,
+}
+
+struct SubdomainClaimedEvent {
+    parent: AssetId,
+    asset_id: AssetId,
+    owner: Identity,
+    fee: u64,
+}
+
+configurable {
+    REGISTRY_CONTRACT_ID: ContractId = ContractId::from(ZERO_B256),
+    DEFAULT_RESOLVER_CONTRACT_ID: ContractId = ContractId::from(ZERO_B256),
+}
+
+storage {
+    // depositor of the parent NFT, the only account allowed to configure and withdraw it
+    parent_owners: StorageMap<AssetId, Identity> = StorageMap {},
+    // claims are closed for parents without terms
+    terms: StorageMap<AssetId, SubdomainTerms> = StorageMap {},
+    // allowlist entries are stored per deposit of the parent, bumping the version on withdraw drops them all
+    allowlist: StorageMap<(AssetId, u64, Identity), bool> = StorageMap {},
+    allowlist_versions: StorageMap<AssetId, u64> = StorageMap {},
+}
+
+#[storage(read)]
+fn only_parent_owner(parent: AssetId) -> Identity {
+    let sender = msg_sender().unwrap();
+    require(storage.parent_owners.get(parent).try_read() == Some(sender), OwnershipError::NotParentOwner);
+    sender
+}
+
+#[storage(read)]
+fn is_allowlisted(parent: AssetId, account: Identity) -> bool {
+    let version = storage.allowlist_versions.get(parent).try_read().unwrap_or(0);
+    storage.allowlist.get((parent, version, account)).try_read().unwrap_or(false)
+}
+
+/// Forwards the fee to the parent owner and refunds the surplus to the sender
+fn collect_fee(terms: SubdomainTerms, parent_owner: Identity, sender: Identity) {
+    if terms.fee == 0 {
+        // coins sent with a free claim would be stuck in the contract
+        require(msg_amount() == 0, ClaimError::WrongFeeAmount);
+        return;
+    }
+    require(msg_asset_id() == terms.fee_asset, ClaimError::WrongFeeAsset);
+    let paid = msg_amount();
+    require(paid >= terms.fee, ClaimError::WrongFeeAmount);
+    transfer(parent_owner, terms.fee_asset, terms.fee);
+    if paid > terms.fee {
+        transfer(sender, terms.fee_asset, paid - terms.fee);
+    }
+}
+
+impl SubdomainRegistrar for Contract {
+    #[payable]
+    #[storage(read, write)]
+    fn deposit_domain(domain: String) {
+        let registry_contract = abi(DomainRegistry, REGISTRY_CONTRACT_ID.into());
+        let parent = registry_contract.get_domain_asset_id(domain);
+        require(msg_asset_id() == parent && msg_amount() == 1, DepositError::WrongDomainAsset);
+        require(registry_contract.is_domain_active(parent), DepositError::DomainNotActive);
+        let owner = msg_sender().unwrap();
+        storage.parent_owners.insert(parent, owner);
+        log(DomainDepositedEvent { parent, owner });
+    }
+
+    #[storage(read, write)]
+    fn withdraw_domain(parent: AssetId) {
+        let owner = only_parent_owner(parent);
+        let _ = storage.parent_owners.remove(parent);
+        // a later deposit, possibly by a new owner of the domain, starts with claims closed and an empty allowlist
+        let _ = storage.terms.remove(parent);
+        storage.allowlist_versions.insert(parent, storage.allowlist_versions.get(parent).try_read().unwrap_or(0) + 1);
+        transfer(owner, parent, 1);
+        log(DomainWithdrawnEvent { parent, owner });
+    }
+
+    #[storage(read, write)]
+    fn set_subdomain_terms(parent: AssetId, terms: Option<SubdomainTerms>) {
+        let _ = only_parent_owner(parent);
+        match terms {
+            Some(t) => storage.terms.insert(parent, t),
+            None => {
+                let _ = storage.terms.remove(parent);
+            },
+        }
+        log(SetSubdomainTermsEvent { parent, terms });
+    }
+
+    #[storage(read, write)]
+    fn set_allowlisted(parent: AssetId, account: Identity, allowed: bool) {
+        let _ = only_parent_owner(parent);
+        let version = storage.allowlist_versions.get(parent).try_read().unwrap_or(0);
+        storage.allowlist.insert((parent, version, account), allowed);
+        log(SetAllowlistedEvent { parent, account, allowed });
+    }
+
+    #[storage(read)]
+    fn revoke_subdomain(parent_domain: String, name: String) {
+        let registry_contract = abi(DomainRegistry, REGISTRY_CONTRACT_ID.into());
+        let _ = only_parent_owner(registry_contract.get_domain_asset_id(parent_domain));
+        registry_contract.revoke_sub_domain(name, parent_domain);
+    }
+
+    #[payable]
+    #[storage(read, write)]
+    fn claim_subdomain(parent_domain: String, name: String) -> AssetId {
+        let registry_contract = abi(DomainRegistry, REGISTRY_CONTRACT_ID.into());
+        let parent = registry_contract.get_domain_asset_id(parent_domain);
+        let parent_owner = storage.parent_owners.get(parent).try_read();
+        require(parent_owner.is_some(), ClaimError::ParentNotDeposited);
+        let terms = storage.terms.get(parent).try_read();
+        require(terms.is_some(), ClaimError::ClaimsClosed);
+        let terms = terms.unwrap();
+        let sender = msg_sender().unwrap();
+        require(
+            !terms.allowlist_only || is_allowlisted(parent, sender),
+            ClaimError::NotAllowlisted
+        );
+        collect_fee(terms, parent_owner.unwrap(), sender);
+        // without inheritance the subdomain keeps the parent expiration from the time of the claim
+        let asset_id = registry_contract.register_sub_domain_with_options(
+            sender,
+            parent_domain,
+            name,
+            None,
+            None,
+            DEFAULT_RESOLVER_CONTRACT_ID,
+            terms.options
+        );
+        log(SubdomainClaimedEvent { parent, asset_id, owner: sender, fee: terms.fee });
+        asset_id
+    }
+
+    #[storage(read)]
+    fn get_parent_owner(parent: AssetId) -> Option<Identity> {
+        storage.parent_owners.get(parent).try_read()
+    }
+
+    #[storage(read)]
+    fn get_subdomain_terms(parent: AssetId) -> Option<SubdomainTerms> {
+        storage.terms.get(parent).try_read()
+    }
+
+    #[storage(read)]
+    fn is_allowlisted(parent: AssetId, account: Identity) -> bool {
+        is_allowlisted(parent, account)
+    }
+}